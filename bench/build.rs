@@ -0,0 +1,113 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{path::Path, process::Command};
+
+const SP1_ELF: &str = "riscv32im-succinct-zkvm-elf";
+
+/// Commit of the guest sources, suffixed with `-dirty` when they have uncommitted changes.
+fn common_git_hash(manifest_dir: &str) -> String {
+    let repo_dir = Path::new(manifest_dir).join("..");
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(&repo_dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let Some(hash) = git(&["rev-parse", "HEAD"]) else {
+        return "unknown".into();
+    };
+
+    // Rerun after a commit or checkout, changes to the sources rerun it anyway.
+    for path in ["HEAD", "refs/heads"] {
+        if let Some(path) = git(&["rev-parse", "--path-format=absolute", "--git-path", path]) {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
+    match git(&[
+        "status",
+        "--porcelain",
+        "--",
+        "common",
+        "guest-r0",
+        "guest-sp1",
+        // Written by `cargo prove build` below
+        ":(exclude)guest-sp1/elf",
+    ]) {
+        Some(status) if status.is_empty() => hash,
+        _ => format!("{}-dirty", hash),
+    }
+}
+
+/// Builds the SP1 guest with `cargo prove build`, the same way `risc0_build::embed_methods`
+/// builds the r0 guest, and copies the ELF into `OUT_DIR`.
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let guest_dir = Path::new(&manifest_dir).join("../guest-sp1");
+    let common_dir = Path::new(&manifest_dir).join("../common");
+
+    for path in [
+        guest_dir.join("src"),
+        guest_dir.join("Cargo.toml"),
+        common_dir.join("src"),
+        common_dir.join("Cargo.toml"),
+    ] {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    println!("cargo:rerun-if-env-changed=SP1_SKIP_BUILD");
+    println!(
+        "cargo:rustc-env=COMMON_GIT_HASH={}",
+        common_git_hash(&manifest_dir)
+    );
+
+    let elf_path = Path::new(&out_dir).join(SP1_ELF);
+
+    if std::env::var("SP1_SKIP_BUILD").is_ok() {
+        println!("cargo:warning=SP1_SKIP_BUILD is set, the SP1 guest is not built");
+        std::fs::write(&elf_path, []).unwrap();
+        return;
+    }
+
+    let toolchain = Command::new("cargo").args(["prove", "--version"]).output();
+    if !matches!(toolchain, Ok(output) if output.status.success()) {
+        panic!(
+            "The SP1 toolchain is required to build the SP1 guest, but `cargo prove` was not found. \
+             Install it with `sp1up` (https://succinctlabs.github.io/sp1/getting-started/install.html), \
+             or set SP1_SKIP_BUILD=1 to build without the SP1 guest."
+        );
+    }
+
+    // The guest is built for the zkVM target, so drop the flags meant for the host build.
+    let status = Command::new("cargo")
+        .args(["prove", "build"])
+        .current_dir(&guest_dir)
+        .env_remove("RUSTC")
+        .env_remove("RUSTC_WORKSPACE_WRAPPER")
+        .env_remove("RUSTFLAGS")
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("CARGO_TARGET_DIR")
+        .status()
+        .expect("Run cargo prove build");
+    if !status.success() {
+        panic!("Failed to build the SP1 guest in {}", guest_dir.display());
+    }
+
+    std::fs::copy(guest_dir.join("elf").join(SP1_ELF), &elf_path).expect("Copy SP1 guest ELF");
+}
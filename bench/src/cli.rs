@@ -12,13 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand, ValueEnum};
 use common::*;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::{filter, fmt, layer::Layer, prelude::*, Registry};
 
-use crate::{metric::*, VmArgs};
+use crate::{cycle_tracker::CycleTracker, metric::*, plot, report, schema, sqlite, VmArgs};
 
 #[derive(Clone, Debug, Eq, PartialEq, ValueEnum)]
 pub enum Vm {
@@ -35,36 +35,116 @@ impl std::fmt::Display for Vm {
     }
 }
 
+/// Where `measure` stores its measurement.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Store {
+    /// One CSV file per experiment, combined by `merge`
+    Csv,
+    /// measurements.sqlite in the data directory
+    Sqlite,
+}
+
+/// Part of the measurement workflow, selected with `--phases`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Phase {
+    /// Run the executor
+    Exec,
+    /// Segmented proof
+    Prove,
+    /// Reduced proof, requires `prove`
+    Reduce,
+    /// Wrapped proof, requires `reduce`
+    Wrap,
+    /// Every phase
+    All,
+}
+
+impl Phase {
+    pub const WORKFLOW: [Phase; 4] = [Phase::Exec, Phase::Prove, Phase::Reduce, Phase::Wrap];
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::result::Result<(), ::std::fmt::Error> {
+        match *self {
+            Phase::Exec => f.write_str("exec"),
+            Phase::Prove => f.write_str("prove"),
+            Phase::Reduce => f.write_str("reduce"),
+            Phase::Wrap => f.write_str("wrap"),
+            Phase::All => f.write_str("all"),
+        }
+    }
+}
+
 impl Vm {
     pub fn measure<X>(
         &self,
+        meta: &mut Meta,
         metrics: &mut Metrics,
         vm_args: VmArgs,
         input: X,
         extra_input: Vec<u32>,
     ) -> Result<()>
     where
-        X: Clone + Runnable + serde::Serialize,
-        BenchmarkInput: From<X>,
+        X: Clone + Into<BenchmarkInput> + Runnable + serde::Serialize,
     {
         // Measure input size
         {
-            let input = BenchmarkInput::from(input.clone());
+            let input: BenchmarkInput = input.clone().into();
             let base_input_bytes = bincode::serialize(&input)?.len() as u128;
             let extra_input_bytes = extra_input.len() as u128 * 4;
             metrics.input_bytes = Some(base_input_bytes + extra_input_bytes);
         }
 
+        let skipped_phases = vm_args.skipped_phases();
+        if !skipped_phases.is_empty() {
+            let skipped_phases: Vec<String> = skipped_phases
+                .iter()
+                .map(|phase| phase.to_string())
+                .collect();
+            metrics.skipped_phases = Some(skipped_phases.join(";"));
+        }
+
+        // The guest verifies the inner proofs even when it is only executed, so they would be
+        // proven in full just to count cycles.
+        if !input.inner().is_empty() && !vm_args.runs(Phase::Prove) && !vm_args.fake_proofs {
+            bail!("The inner proofs of this benchmark need the prove phase or --fake-proofs");
+        }
+
         // Measure VM performance
         match self {
             Vm::R0 => crate::r0::measure(metrics, vm_args, input, extra_input),
-            Vm::SP1 => crate::sp1::measure(metrics, vm_args, input, extra_input),
+            Vm::SP1 => crate::sp1::measure(meta, metrics, vm_args, input, extra_input),
         }
     }
 }
 
+struct Measure<'a> {
+    vm: &'a Vm,
+    meta: &'a mut Meta,
+    metrics: &'a mut Metrics,
+    vm_args: VmArgs,
+}
+
+impl BenchmarkVisitor for Measure<'_> {
+    type Output = Result<()>;
+
+    fn visit<B: Benchmark>(self, benchmark: &B) -> Result<()> {
+        let (input, extra_input) = benchmark.input();
+        self.vm
+            .measure(self.meta, self.metrics, self.vm_args, input, extra_input)
+    }
+}
+
+fn parse_column(column: &str) -> Result<String> {
+    report::check_column(column)?;
+    Ok(column.into())
+}
+
 #[derive(Subcommand)]
 pub enum Command {
+    /// List every registered benchmark with its parameters
+    List,
+
     Measure {
         machine_tag: String,
 
@@ -79,9 +159,82 @@ pub enum Command {
         #[arg(long, require_equals = true)]
         sp1_shard_batch_size: Option<u32>,
 
+        /// Phases to run, e.g. `exec` to only count cycles
+        #[arg(
+            long,
+            require_equals = true,
+            value_delimiter = ',',
+            default_value = "all"
+        )]
+        phases: Vec<Phase>,
+
+        /// Skip real proving with the r0 dev mode prover, for testing the harness. sp1 has no
+        /// such prover and only executes
+        #[arg(long)]
+        fake_proofs: bool,
+
+        #[arg(long, require_equals = true, default_value = "csv")]
+        store: Store,
+
         #[command(subcommand)]
         benchmark: BenchmarkParam,
     },
+
+    /// Combine the measurements of every experiment into measurements.csv
+    Merge,
+
+    /// Print comparison tables of the measurements of every experiment
+    Report {
+        #[arg(long, require_equals = true, default_value = "table")]
+        format: report::Format,
+
+        #[command(flatten)]
+        filter: report::Filter,
+    },
+
+    /// Render log-log SVG charts of the measurements, one per benchmark and params
+    Plot {
+        /// Column on the x axis
+        #[arg(
+            long,
+            require_equals = true,
+            default_value = "benchmark_size",
+            value_parser = parse_column
+        )]
+        x: String,
+
+        /// Column on the y axis
+        #[arg(
+            long,
+            require_equals = true,
+            default_value = "segmented_prove_millis",
+            value_parser = parse_column
+        )]
+        y: String,
+
+        /// Columns whose values tell the lines of a chart apart
+        #[arg(
+            long,
+            require_equals = true,
+            value_delimiter = ',',
+            default_value = "machine_tag,vm,segment_size",
+            value_parser = parse_column
+        )]
+        group_by: Vec<String>,
+
+        #[command(flatten)]
+        filter: report::Filter,
+
+        /// Directory of the charts, defaults to `plots` in the data directory
+        #[arg(long, require_equals = true)]
+        outdir: Option<std::path::PathBuf>,
+    },
+
+    /// Import measurements CSV files into measurements.sqlite
+    Import {
+        /// Defaults to measurements.csv in the data directory
+        files: Vec<std::path::PathBuf>,
+    },
 }
 
 #[derive(Parser)]
@@ -98,25 +251,62 @@ pub struct Cli {
 impl Cli {
     pub fn run(&self) -> Result<()> {
         match &self.command {
+            Command::List => self.list(),
+            Command::Merge => self.merge()?,
+            Command::Import { files } => self.import(files)?,
+            Command::Report { format, filter } => self.report(*format, filter)?,
+            Command::Plot {
+                x,
+                y,
+                group_by,
+                filter,
+                outdir,
+            } => self.plot(x, y, group_by, filter, outdir.as_deref())?,
             Command::Measure {
                 machine_tag,
                 vm,
                 segment_size,
                 sp1_save_disk_threshold,
                 sp1_shard_batch_size,
+                phases,
+                fake_proofs,
+                store,
                 benchmark,
             } => {
                 let vm_args = VmArgs {
                     segment_size: *segment_size,
                     sp1_save_disk_threshold: *sp1_save_disk_threshold,
                     sp1_shard_batch_size: *sp1_shard_batch_size,
+                    phases: phases.clone(),
+                    fake_proofs: *fake_proofs,
                 };
-                self.measure(machine_tag.clone(), vm, vm_args, benchmark)?
+                for (phase, requires) in
+                    [(Phase::Reduce, Phase::Prove), (Phase::Wrap, Phase::Reduce)]
+                {
+                    if vm_args.runs(phase) && !vm_args.runs(requires) {
+                        bail!("Phase {} requires phase {}", phase, requires);
+                    }
+                }
+                self.measure(machine_tag.clone(), vm, vm_args, *store, benchmark)?
             }
         }
         Ok(())
     }
 
+    fn list(&self) {
+        let benchmarks = BenchmarkParam::augment_subcommands(clap::Command::new("benchmark"));
+        for benchmark in benchmarks.get_subcommands() {
+            let usage: Vec<String> = std::iter::once(benchmark.get_name().to_string())
+                .chain(
+                    benchmark
+                        .get_positionals()
+                        .map(|arg| format!("<{}>", arg.get_id().as_str().to_uppercase())),
+                )
+                .collect();
+            println!("{}", usage.join(" "));
+        }
+    }
+
     pub fn log_file_path(
         &self,
         machine_tag: &String,
@@ -131,52 +321,224 @@ impl Cli {
         .collect()
     }
 
+    /// Measurement of a single experiment, written once by `measure`.
+    pub fn experiment_file_path(
+        &self,
+        machine_tag: &String,
+        experiment_id: &ExperimentId,
+    ) -> std::path::PathBuf {
+        [
+            &self.datadir,
+            machine_tag,
+            &format!("{}.csv", experiment_id),
+        ]
+        .iter()
+        .collect()
+    }
+
+    /// Canonical table of every measurement, written by `merge`.
     pub fn measurements_file_path(&self) -> std::path::PathBuf {
         [&self.datadir, "measurements.csv"].iter().collect()
     }
 
-    fn read_measurements(&self) -> Result<Vec<Measurement>> {
-        let mut measurements: Vec<Measurement> = Vec::new();
+    pub fn sqlite_file_path(&self) -> std::path::PathBuf {
+        [&self.datadir, "measurements.sqlite"].iter().collect()
+    }
+
+    /// Rows of `measurements.csv` that could not be read, kept for manual recovery.
+    pub fn rejected_file_path(&self) -> std::path::PathBuf {
+        [&self.datadir, "measurements.rejected.csv"]
+            .iter()
+            .collect()
+    }
 
+    fn read_measurements(&self) -> Result<Vec<Measurement>> {
         let csv_file = match std::fs::OpenOptions::new()
             .read(true)
             .open(self.measurements_file_path())
             .context("Open CSV file")
         {
             Ok(csv_file) => csv_file,
-            Err(_) => return Ok(measurements),
+            Err(_) => return Ok(vec![]),
         };
 
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_reader(csv_file);
-        for row in rdr.deserialize::<Measurement>() {
-            if let Ok(measurement) = row {
-                measurements.push(measurement)
+        let (measurements, rejected) = schema::read(csv_file)?;
+
+        // The rows would be lost when the file is rewritten, so move them aside.
+        if !rejected.is_empty() {
+            let rejected_file = std::fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(self.rejected_file_path())
+                .context("Open rejected rows file")?;
+            let mut wtr = csv::WriterBuilder::new()
+                .flexible(true)
+                .from_writer(rejected_file);
+
+            for row in &rejected {
+                let msg = format!(
+                    "Line {} of {} can not be read: {:#}",
+                    row.line,
+                    self.measurements_file_path().display(),
+                    row.error
+                );
+                warn!("{}", msg);
+                eprintln!("Warning: {}", msg);
+
+                wtr.write_record(&row.record)
+                    .context("Write rejected row")?;
             }
+            wtr.flush().context("Flush rejected rows")?;
+
+            eprintln!(
+                "Warning: moved {} rows to {}",
+                rejected.len(),
+                self.rejected_file_path().display()
+            );
         }
 
         Ok(measurements)
     }
 
-    pub fn write_measurements(&self, measurements: &Vec<Measurement>) -> Result<()> {
-        let csv_file = std::fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(self.measurements_file_path())
-            .context("Create CSV file")?;
+    pub fn write_measurements(&self, measurements: &[Measurement]) -> Result<()> {
+        write_atomic(&self.measurements_file_path(), |csv_file| {
+            schema::write(csv_file, measurements)
+        })
+    }
 
-        let mut wtr = csv::WriterBuilder::new().from_writer(csv_file);
-        for measurement in measurements {
-            wtr.serialize(measurement)
-                .context("Serialize metric into CSV")?;
+    /// Reads a CSV file, warning about rows that can not be read.
+    fn read_csv_file(path: &std::path::Path) -> Result<Vec<Measurement>> {
+        let csv_file =
+            std::fs::File::open(path).with_context(|| format!("Open {}", path.display()))?;
+        let (measurements, rejected) = schema::read(csv_file)?;
+
+        for row in &rejected {
+            eprintln!(
+                "Warning: line {} of {} can not be read: {:#}",
+                row.line,
+                path.display(),
+                row.error
+            );
         }
-        wtr.flush().context("Flush CSV")?;
+
+        Ok(measurements)
+    }
+
+    /// Adds the measurements of experiment files whose experiment is not in `measurements`.
+    fn add_experiments(&self, measurements: &mut Vec<Measurement>) -> Result<usize> {
+        let mut experiment_ids: std::collections::HashSet<String> = measurements
+            .iter()
+            .map(|Measurement(meta, _)| meta.experiment_id.clone())
+            .collect();
+
+        let pattern = [&glob::Pattern::escape(&self.datadir), "*", "*.csv"]
+            .iter()
+            .collect::<std::path::PathBuf>();
+        let mut added = 0;
+        for path in glob::glob(&pattern.to_string_lossy()).context("List experiment files")? {
+            let path = path.context("List experiment files")?;
+            for measurement in Self::read_csv_file(&path)? {
+                if experiment_ids.insert(measurement.0.experiment_id.clone()) {
+                    measurements.push(measurement);
+                    added += 1;
+                }
+            }
+        }
+
+        measurements.sort_by(|Measurement(a, _), Measurement(b, _)| a.datetime.cmp(&b.datetime));
+        Ok(added)
+    }
+
+    /// Measurements of measurements.csv and of the experiments not merged into it yet.
+    pub fn stored_measurements(&self) -> Result<Vec<Measurement>> {
+        let mut measurements = match self.measurements_file_path() {
+            path if path.exists() => Self::read_csv_file(&path)?,
+            _ => vec![],
+        };
+        self.add_experiments(&mut measurements)?;
+        Ok(measurements)
+    }
+
+    /// Adds the measurements of experiments missing from measurements.csv, which keeps the rows
+    /// of experiments whose files were removed.
+    fn merge(&self) -> Result<()> {
+        let mut measurements = self.read_measurements()?;
+        let merged = self.add_experiments(&mut measurements)?;
+        self.write_measurements(&measurements)?;
+
+        println!(
+            "Merged {} new measurements into {}",
+            merged,
+            self.measurements_file_path().display()
+        );
+
         Ok(())
     }
 
-    fn setup_experiment(&self, machine_tag: &String) -> Result<ExperimentId> {
+    /// Inserts the measurements of CSV files into measurements.sqlite, replacing rows of the
+    /// same experiments.
+    fn import(&self, files: &[std::path::PathBuf]) -> Result<()> {
+        let files = match files {
+            [] => vec![self.measurements_file_path()],
+            files => files.to_vec(),
+        };
+
+        std::fs::create_dir_all(&self.datadir).context("Create data directory")?;
+        let mut conn = sqlite::open(&self.sqlite_file_path())?;
+
+        for path in files {
+            let measurements = Self::read_csv_file(&path)?;
+            for measurement in &measurements {
+                sqlite::insert(&mut conn, measurement)?;
+            }
+
+            println!(
+                "Imported {} measurements from {} into {}",
+                measurements.len(),
+                path.display(),
+                self.sqlite_file_path().display()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn report(&self, format: report::Format, filter: &report::Filter) -> Result<()> {
+        let measurements = filter.apply(self.stored_measurements()?)?;
+        report::write(std::io::stdout().lock(), &measurements, format)
+    }
+
+    fn plot(
+        &self,
+        x: &str,
+        y: &str,
+        group_by: &[String],
+        filter: &report::Filter,
+        outdir: Option<&std::path::Path>,
+    ) -> Result<()> {
+        let measurements = filter.apply(self.stored_measurements()?)?;
+        let outdir = match outdir {
+            Some(outdir) => outdir.to_path_buf(),
+            None => [&self.datadir, "plots"].iter().collect(),
+        };
+
+        std::fs::create_dir_all(&outdir).context("Create plot directory")?;
+        for path in plot::write(&outdir, &measurements, x, y, group_by)? {
+            println!("{}", path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Creates the experiment and directs the logs of every thread to its log file until the
+    /// returned guard is dropped.
+    fn setup_experiment(&self, machine_tag: &String) -> Result<(ExperimentId, ExperimentLog)> {
+        // Experiments in one process, e.g. in tests, run one at a time so that each log file
+        // only has the logs of its own experiment.
+        let running = EXPERIMENT_RUNNING
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
         let experiment_id = ExperimentId::new();
         println!("{}", experiment_id);
 
@@ -191,15 +553,28 @@ impl Cli {
             .open(log_file_path)
             .context("Create log file")?;
 
-        let subscriber = Registry::default().with(
-            fmt::layer()
-                .with_ansi(false)
-                .with_writer(log_file)
-                .with_filter(filter::LevelFilter::from_level(tracing::Level::INFO)),
-        );
-        tracing::subscriber::set_global_default(subscriber)?;
+        // The global subscriber can only be set once per process, so it writes to whichever
+        // log file is current.
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            let subscriber = Registry::default()
+                .with(
+                    fmt::layer()
+                        .with_ansi(false)
+                        .with_writer(|| LogWriter)
+                        .with_filter(filter::LevelFilter::from_level(tracing::Level::INFO)),
+                )
+                .with(
+                    CycleTracker.with_filter(filter::LevelFilter::from_level(tracing::Level::INFO)),
+                );
+            // Also forwards `log` records of the provers
+            if let Err(err) = subscriber.try_init() {
+                eprintln!("Warning: experiment logs are not recorded: {}", err);
+            }
+        });
+        *lock_log_file() = Some(log_file);
 
-        Ok(experiment_id)
+        Ok((experiment_id, ExperimentLog { _running: running }))
     }
 
     fn measure(
@@ -207,12 +582,12 @@ impl Cli {
         machine_tag: String,
         vm: &Vm,
         vm_args: VmArgs,
+        store: Store,
         benchmark: &BenchmarkParam,
     ) -> Result<()> {
-        let experiment_id = self.setup_experiment(&machine_tag)?;
-        let mut measurements = self.read_measurements()?;
+        let (experiment_id, _experiment_log) = self.setup_experiment(&machine_tag)?;
 
-        let meta = {
+        let mut meta = {
             let datetime = chrono::Utc::now().to_rfc3339();
 
             #[allow(unused_assignments, unused_mut)]
@@ -229,17 +604,27 @@ impl Cli {
             }
 
             Meta {
+                schema_version: schema::SCHEMA_VERSION,
                 machine_tag,
                 benchmark_name: benchmark.name(),
                 benchmark_size: benchmark.size(),
+                benchmark_params: serde_json::to_string(&benchmark.params())?,
                 vm: vm.to_string(),
                 segment_size: vm_args.segment_size,
+                fake_proofs: vm_args.fake_proofs,
                 experiment_id: experiment_id.to_string(),
                 datetime,
                 r0_feature_flags,
                 r0_version: risc0_zkvm::VERSION.into(),
+                r0_elf_sha256: crate::r0::elf_sha256(),
+                r0_image_id: crate::r0::image_id(),
                 sp1_save_disk_threshold: vm_args.sp1_save_disk_threshold,
                 sp1_shard_batch_size: vm_args.sp1_shard_batch_size,
+                sp1_elf_sha256: crate::sp1::elf_sha256(),
+                // Filled in by the SP1 measurement, the key is only known after setup
+                sp1_vk_hash: None,
+                common_version: common::VERSION.into(),
+                common_git_hash: common_git_hash(),
                 cli_args: std::env::args().collect::<Vec<_>>().join(" "),
             }
         };
@@ -249,34 +634,115 @@ impl Cli {
         let mut metrics = Metrics::default();
 
         // Perform the measurement
-        let measure_res = match benchmark {
-            BenchmarkParam::BigInput(param) => {
-                vm.measure(&mut metrics, vm_args, param.input(), vec![])
-            }
-            BenchmarkParam::BigInputPush(param) => {
-                let (input, extra_input) = param.input();
-                vm.measure(&mut metrics, vm_args, input, extra_input)
-            }
-            BenchmarkParam::BigInputVecless(param) => {
-                let (input, extra_input) = param.input();
-                vm.measure(&mut metrics, vm_args, input, extra_input)
-            }
-            BenchmarkParam::Fib(param) => vm.measure(&mut metrics, vm_args, param.input(), vec![]),
-            BenchmarkParam::HelloWorld(param) => {
-                vm.measure(&mut metrics, vm_args, param.input(), vec![])
-            }
-            BenchmarkParam::Sha2(param) => vm.measure(&mut metrics, vm_args, param.input(), vec![]),
-            BenchmarkParam::Sort(param) => vm.measure(&mut metrics, vm_args, param.input(), vec![]),
-        };
+        let measure_res = benchmark.visit(Measure {
+            vm,
+            meta: &mut meta,
+            metrics: &mut metrics,
+            vm_args,
+        });
 
         if let Err(err) = measure_res {
             error!("Error when taking measurements: {:?}", err);
             metrics.error_string = Some(serde_json::to_string(&format!("{:?}", err))?);
         }
 
-        measurements.push(Measurement(meta, metrics));
-        self.write_measurements(&measurements)?;
+        let measurement = Measurement(meta, metrics);
+        // Every experiment has its own file, so parallel runs never write the same file.
+        let experiment_file_path =
+            self.experiment_file_path(&measurement.0.machine_tag, &experiment_id);
+        match store {
+            Store::Csv => {
+                write_atomic(&experiment_file_path, |csv_file| {
+                    schema::write(csv_file, &[measurement])
+                })?;
+            }
+            Store::Sqlite => {
+                let stored = sqlite::open(&self.sqlite_file_path())
+                    .and_then(|mut conn| sqlite::insert(&mut conn, &measurement));
+                if let Err(err) = stored {
+                    // Keep the measurement, `import` can add its file to the database later
+                    write_atomic(&experiment_file_path, |csv_file| {
+                        schema::write(csv_file, &[measurement])
+                    })?;
+                    return Err(err.context(format!(
+                        "Store in SQLite, wrote {} instead",
+                        experiment_file_path.display()
+                    )));
+                }
+            }
+        }
 
         Ok(())
     }
 }
+
+/// Held while an experiment runs.
+static EXPERIMENT_RUNNING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Log file of the running experiment, if any.
+static LOG_FILE: std::sync::Mutex<Option<std::fs::File>> = std::sync::Mutex::new(None);
+
+fn lock_log_file() -> std::sync::MutexGuard<'static, Option<std::fs::File>> {
+    LOG_FILE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Writes logs to the log file of the running experiment, and drops them between experiments.
+struct LogWriter;
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match lock_log_file().as_mut() {
+            Some(log_file) => log_file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match lock_log_file().as_mut() {
+            Some(log_file) => log_file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Directs logs to the experiment's log file until dropped.
+struct ExperimentLog {
+    _running: std::sync::MutexGuard<'static, ()>,
+}
+
+impl Drop for ExperimentLog {
+    fn drop(&mut self) {
+        *lock_log_file() = None;
+    }
+}
+
+/// Writes to a temporary file and renames it over `path`, so readers and crashes never see a
+/// partially written file.
+fn write_atomic<F>(path: &std::path::Path, f: F) -> Result<()>
+where
+    F: FnOnce(&mut std::fs::File) -> Result<()>,
+{
+    let tmp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+
+    let mut file = std::fs::File::create(&tmp_path).context("Create temporary file")?;
+    f(&mut file)?;
+    file.sync_all().context("Sync temporary file")?;
+
+    std::fs::rename(&tmp_path, path).context("Rename temporary file")?;
+
+    // Persist the rename itself, which lives in the directory entry
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => std::path::Path::new("."),
+        };
+        std::fs::File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .context("Sync directory of renamed file")?;
+    }
+
+    Ok(())
+}
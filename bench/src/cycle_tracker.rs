@@ -0,0 +1,91 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cycles of the guest regions marked with `cycle-tracker-start/end`, for VMs without an
+//! in-guest cycle counter.
+//!
+//! The SP1 runtime reports each region only in its logs, as a `┌╴region` line when it starts
+//! and a `└╴1,234 cycles` line when it ends. [CycleTracker] is a layer of the global subscriber
+//! that keeps these lines while [collect] runs.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::layer::{Context, Layer};
+
+static LINES: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+fn lock_lines() -> std::sync::MutexGuard<'static, Option<Vec<String>>> {
+    LINES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+pub struct CycleTracker;
+
+impl<S: Subscriber> Layer<S> for CycleTracker {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        struct Message(Option<String>);
+
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+
+        if let Some(lines) = lock_lines().as_mut() {
+            let mut message = Message(None);
+            event.record(&mut message);
+            if let Some(message) = message.0 {
+                if message.contains("┌╴") || message.contains("└╴") {
+                    lines.push(message);
+                }
+            }
+        }
+    }
+}
+
+/// Runs `f` and returns the cycles of every region that ended while it ran.
+pub fn collect<X>(f: impl FnOnce() -> X) -> (X, HashMap<String, u64>) {
+    *lock_lines() = Some(vec![]);
+    let out = f();
+    let lines = lock_lines().take().unwrap_or_default();
+
+    (out, region_cycles(&lines))
+}
+
+fn region_cycles(lines: &[String]) -> HashMap<String, u64> {
+    let mut open = vec![];
+    let mut cycles = HashMap::new();
+    for line in lines {
+        if let Some((_, region)) = line.split_once("┌╴") {
+            open.push(region.trim().to_string());
+        } else if let Some((_, count)) = line.split_once("└╴") {
+            let count: String = count
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == ',')
+                .filter(char::is_ascii_digit)
+                .collect();
+            if let (Some(region), Ok(count)) = (open.pop(), count.parse()) {
+                cycles.insert(region, count);
+            }
+        }
+    }
+    cycles
+}
@@ -0,0 +1,47 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod cli;
+pub mod cycle_tracker;
+pub mod metric;
+pub mod plot;
+mod r0;
+pub mod report;
+pub mod schema;
+mod sp1;
+pub mod sqlite;
+
+use cli::Phase;
+
+pub struct VmArgs {
+    pub segment_size: u32,
+    pub sp1_save_disk_threshold: Option<u32>,
+    pub sp1_shard_batch_size: Option<u32>,
+    pub phases: Vec<Phase>,
+    pub fake_proofs: bool,
+}
+
+impl VmArgs {
+    pub fn runs(&self, phase: Phase) -> bool {
+        self.phases.contains(&Phase::All) || self.phases.contains(&phase)
+    }
+
+    /// Phases that are not run, in workflow order.
+    pub fn skipped_phases(&self) -> Vec<Phase> {
+        Phase::WORKFLOW
+            .into_iter()
+            .filter(|phase| !self.runs(*phase))
+            .collect()
+    }
+}
@@ -34,35 +34,64 @@ impl ExperimentId {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl Default for ExperimentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Meta {
+    pub schema_version: u32,
     pub machine_tag: String,
     pub benchmark_name: String,
     pub benchmark_size: u32,
+    pub benchmark_params: String,
     pub vm: String,
     pub segment_size: u32,
+    pub fake_proofs: bool,
     pub experiment_id: String,
     pub datetime: String,
     pub r0_feature_flags: Option<String>,
     pub r0_version: String,
+    pub r0_elf_sha256: String,
+    pub r0_image_id: String,
     pub sp1_save_disk_threshold: Option<u32>,
     pub sp1_shard_batch_size: Option<u32>,
+    pub sp1_elf_sha256: String,
+    pub sp1_vk_hash: Option<String>,
+    pub common_version: String,
+    pub common_git_hash: String,
     pub cli_args: String,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Metrics {
     pub error_string: Option<String>,
+    /// Metrics, or whole phases, the VM cannot measure, separated by `;`, to tell them apart
+    /// from missing ones.
+    pub unsupported: Option<String>,
+    /// Phases not run because of `--phases`, separated by `;`.
+    pub skipped_phases: Option<String>,
     pub input_bytes: Option<u128>,
 
     pub exec_millis: Option<u128>,
     pub exec_user_cycles: Option<u64>,
+    pub exec_decode_cycles: Option<u64>,
+    pub exec_compute_cycles: Option<u64>,
+    pub exec_commit_cycles: Option<u64>,
     pub exec_output: Option<String>,
 
     pub prover_create_millis: Option<u128>,
 
+    /// Proving the inner proofs of a composition benchmark, up to their reduced form.
+    pub inner_prove_millis: Option<u128>,
+    pub inner_proofs: Option<u64>,
+
     pub segmented_prove_millis: Option<u128>,
-    pub segmented_poof_segments: Option<u64>,
+    pub segmented_proof_segments: Option<u64>,
+    /// JSON array of [Segment]
+    pub segmented_segments: Option<String>,
     pub segmented_proof_kind: Option<String>,
     pub segmented_proof_output: Option<String>,
     pub segmented_verify_millis: Option<u128>,
@@ -70,13 +99,50 @@ pub struct Metrics {
     pub segmented_proof_bytes: Option<u128>,
 
     pub reduced_prove_millis: Option<u128>,
+    pub reduced_lift_millis: Option<u128>,
+    pub reduced_lifts: Option<u64>,
+    pub reduced_join_millis: Option<u128>,
+    pub reduced_joins: Option<u64>,
+    pub reduced_resolve_millis: Option<u128>,
+    pub reduced_resolves: Option<u64>,
     pub reduced_proof_kind: Option<String>,
     pub reduced_verify_millis: Option<u128>,
     pub reduced_proof_w_metadata_bytes: Option<u128>,
     pub reduced_proof_bytes: Option<u128>,
+
+    pub wrapped_prove_millis: Option<u128>,
+    /// Shrinking the reduced STARK before wrapping it, SP1 only.
+    pub wrapped_shrink_millis: Option<u128>,
+    /// Proving the STARK again over the BN254 field.
+    pub wrapped_stark_bn254_millis: Option<u128>,
+    /// Proving the BN254 STARK with the SNARK.
+    pub wrapped_snark_millis: Option<u128>,
+    pub wrapped_proof_kind: Option<String>,
+    pub wrapped_verify_millis: Option<u128>,
+    pub wrapped_proof_bytes: Option<u128>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Breakdown of a single r0 segment or SP1 shard of the segmented proof.
+///
+/// Fields are `None` when the VM does not expose them per segment.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Segment {
+    pub cycles: Option<u64>,
+    pub po2: Option<u32>,
+    pub prove_millis: Option<u128>,
+    pub proof_bytes: Option<u128>,
+}
+
+impl Metrics {
+    pub fn mark_unsupported(&mut self, metric: &str) {
+        self.unsupported = Some(match self.unsupported.take() {
+            Some(unsupported) => format!("{};{}", unsupported, metric),
+            None => metric.into(),
+        });
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Measurement(pub Meta, pub Metrics);
 
 pub fn measure<X, F>(metric: &mut Option<u128>, f: F) -> Result<X>
@@ -89,3 +155,27 @@ where
     *metric = Some(elapsed);
     Ok(out)
 }
+
+/// Like [measure], but adds to the metric, for steps that run several times.
+pub fn measure_add<X, F>(metric: &mut Option<u128>, f: F) -> Result<X>
+where
+    F: FnOnce() -> Result<X>,
+{
+    let start = std::time::Instant::now();
+    let out = f()?;
+    let elapsed = start.elapsed().as_millis();
+    *metric = Some(metric.unwrap_or_default() + elapsed);
+    Ok(out)
+}
+
+/// Lowercase hex encoding, as used for digests in [Meta].
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Commit of the guest sources, suffixed with `-dirty` when they had uncommitted changes.
+///
+/// Captured by the build script, so it describes the sources the guests were built from.
+pub fn common_git_hash() -> String {
+    env!("COMMON_GIT_HASH").into()
+}
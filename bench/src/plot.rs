@@ -0,0 +1,205 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Log-log SVG charts of stored measurements, rendered by the `plot` command.
+//!
+//! Every benchmark gets one chart with a line per group of measurements, e.g. per machine, VM
+//! and segment size, and per guest build. Benchmarks with params besides their size, like the accounts of `mpt`, get
+//! a chart per value of those params. Runs with the same x value are averaged, and values that
+//! are missing or not positive can not be drawn on a log scale, so they are left out.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use plotters::prelude::*;
+use serde_json::{Map, Value};
+
+use crate::{metric::Measurement, report};
+
+type Points = Vec<(f64, f64)>;
+
+fn params(measurement: &Measurement) -> Map<String, Value> {
+    serde_json::from_str(&measurement.0.benchmark_params).unwrap_or_default()
+}
+
+/// Params that hold the benchmark size in every measurement of each benchmark.
+fn size_params(measurements: &[Measurement]) -> HashMap<&str, HashSet<String>> {
+    let mut size_params: HashMap<&str, HashSet<String>> = HashMap::new();
+    for measurement in measurements {
+        let Measurement(meta, _) = measurement;
+        let keys: HashSet<String> = params(measurement)
+            .into_iter()
+            .filter(|(_, val)| val.as_u64() == Some(meta.benchmark_size as u64))
+            .map(|(key, _)| key)
+            .collect();
+        size_params
+            .entry(&meta.benchmark_name)
+            .and_modify(|size_keys| size_keys.retain(|key| keys.contains(key)))
+            .or_insert(keys);
+    }
+    size_params
+}
+
+/// Chart of a measurement: its benchmark name, followed by its params other than the size.
+fn chart_name(measurement: &Measurement, size_params: &HashSet<String>) -> String {
+    let params = params(measurement);
+    std::iter::once(measurement.0.benchmark_name.clone())
+        .chain(
+            params
+                .keys()
+                .filter(|key| !size_params.contains(*key))
+                .map(|key| format!("{}={}", key, report::text(&params, key))),
+        )
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Writes `<benchmark_name>.svg` into `outdir` for every benchmark with points to draw, or
+/// `<benchmark_name>-<param>=<value>.svg` for the params other than the size, and returns the
+/// paths.
+pub fn write(
+    outdir: &Path,
+    measurements: &[Measurement],
+    x: &str,
+    y: &str,
+    group_by: &[String],
+) -> Result<Vec<PathBuf>> {
+    let size_params = size_params(measurements);
+
+    let mut charts: BTreeMap<String, BTreeMap<String, Points>> = BTreeMap::new();
+    for measurement in measurements {
+        let values = report::values(measurement)?;
+        let point = report::number(&values, x).zip(report::number(&values, y));
+        let Some(point) = point.filter(|(x, y)| *x > 0.0 && *y > 0.0) else {
+            continue;
+        };
+
+        let mut label: Vec<String> = group_by
+            .iter()
+            .map(|column| report::text(&values, column))
+            .collect();
+        // Runs of different guest builds are never averaged into one line
+        let build = report::text(&values, "build");
+        if !build.is_empty() && !group_by.iter().any(|column| column == "build") {
+            label.push(build);
+        }
+        let chart = chart_name(
+            measurement,
+            &size_params[measurement.0.benchmark_name.as_str()],
+        );
+        charts
+            .entry(chart)
+            .or_default()
+            .entry(label.join(" "))
+            .or_default()
+            .push(point);
+    }
+
+    let mut paths = vec![];
+    for (chart, lines) in charts {
+        let lines: BTreeMap<String, Points> = lines
+            .into_iter()
+            .map(|(label, points)| (label, mean_by_x(points)))
+            .collect();
+
+        let path = outdir.join(format!("{}.svg", chart));
+        draw(&path, &chart, x, y, &lines).with_context(|| format!("Plot {}", path.display()))?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Points sorted by x, with the mean y of the points that have the same x.
+fn mean_by_x(mut points: Points) -> Points {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut means: Vec<(f64, f64, usize)> = vec![];
+    for (x, y) in points {
+        match means.last_mut() {
+            Some((last_x, sum, n)) if *last_x == x => {
+                *sum += y;
+                *n += 1;
+            }
+            _ => means.push((x, y, 1)),
+        }
+    }
+
+    means
+        .into_iter()
+        .map(|(x, sum, n)| (x, sum / n as f64))
+        .collect()
+}
+
+/// Range of the values with some room around them, as a log scale needs a non-empty range.
+fn range(vals: impl Iterator<Item = f64>) -> Range<f64> {
+    let (min, max) = vals.fold((f64::MAX, f64::MIN), |(min, max), val| {
+        (min.min(val), max.max(val))
+    });
+    min / 1.5..max * 1.5
+}
+
+fn draw(
+    path: &Path,
+    caption: &str,
+    x: &str,
+    y: &str,
+    lines: &BTreeMap<String, Points>,
+) -> Result<()> {
+    let points = || lines.values().flatten();
+    let x_range = range(points().map(|(x, _)| *x));
+    let y_range = range(points().map(|(_, y)| *y));
+
+    let root = SVGBackend::new(path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(caption, ("sans-serif", 30))
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(x_range.log_scale(), y_range.log_scale())?;
+
+    chart.configure_mesh().x_desc(x).y_desc(y).draw()?;
+
+    for (i, (label, points)) in lines.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(
+                points.iter().copied(),
+                color.stroke_width(2),
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        chart.draw_series(
+            points
+                .iter()
+                .map(|point| Circle::new(*point, 4, color.filled())),
+        )?;
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
@@ -12,13 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use common::*;
 use guest_r0::{GUEST_R0_ELF, GUEST_R0_ID};
-use risc0_zkvm::{default_executor, default_prover, ExecutorEnv, ProverOpts};
+use risc0_zkvm::{
+    default_executor, default_prover, get_prover_server, sha::Digestible, stark_to_snark,
+    CompactReceipt, CompactReceiptVerifierParameters, ExecutorEnv, ExecutorEnvBuilder,
+    ExecutorImpl, InnerReceipt, Prover, ProverOpts, Receipt, SessionEvents, VerifierContext,
+};
+use std::{cell::RefCell, rc::Rc, time::Instant};
 use tracing::info;
 
-use crate::{metric, VmArgs};
+use crate::{cli::Phase, metric, VmArgs};
 
 fn proof_kind(receipt: &risc0_zkvm::Receipt) -> String {
     match receipt.inner {
@@ -30,6 +35,103 @@ fn proof_kind(receipt: &risc0_zkvm::Receipt) -> String {
     }
 }
 
+pub fn elf_sha256() -> String {
+    metric::hex(&sha256(GUEST_R0_ELF))
+}
+
+pub fn image_id() -> String {
+    risc0_zkvm::sha::Digest::from(GUEST_R0_ID).to_string()
+}
+
+/// Proves the [Runnable::inner] inputs of a benchmark as succinct receipts of the same guest.
+fn prove_inner(
+    prover: &dyn Prover,
+    segment_size: u32,
+    inputs: Vec<BenchmarkInput>,
+) -> Result<Vec<Receipt>> {
+    inputs
+        .into_iter()
+        .map(|input| {
+            let mut env = ExecutorEnv::builder();
+            env.write(&input).context("Write input for inner proof")?;
+            env.segment_limit_po2(segment_size);
+            let env = env.build().context("Build env for inner proof")?;
+
+            let receipt = prover
+                .prove(env, GUEST_R0_ELF)
+                .context("Inner prove")?
+                .receipt;
+            prover
+                .compress(&ProverOpts::succinct(), &receipt)
+                .context("Inner reduced prove")
+        })
+        .collect()
+}
+
+/// Writes what the guest needs to verify the inner proofs, and adds them as assumptions.
+fn add_inner(env: &mut ExecutorEnvBuilder<'_>, inner: &[Receipt]) -> Result<()> {
+    if inner.is_empty() {
+        return Ok(());
+    }
+
+    let journals: Vec<&Vec<u8>> = inner.iter().map(|receipt| &receipt.journal.bytes).collect();
+    env.write(&GUEST_R0_ID).context("Write inner image ID")?;
+    env.write(&journals).context("Write inner journals")?;
+    for receipt in inner {
+        env.add_assumption(receipt.clone());
+    }
+
+    Ok(())
+}
+
+/// Selects the dev mode prover until dropped, as the pinned [ProverOpts] have no dev mode
+/// option. Restores the previous `RISC0_DEV_MODE`, so it does not leak into later measurements.
+struct DevMode(Option<std::ffi::OsString>);
+
+impl DevMode {
+    fn set() -> Self {
+        let previous = std::env::var_os("RISC0_DEV_MODE");
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        DevMode(previous)
+    }
+}
+
+impl Drop for DevMode {
+    fn drop(&mut self) {
+        match self.0.take() {
+            Some(previous) => std::env::set_var("RISC0_DEV_MODE", previous),
+            None => std::env::remove_var("RISC0_DEV_MODE"),
+        }
+    }
+}
+
+/// Records each segment of [risc0_zkvm::ProverServer::prove_session], in proving order.
+#[derive(Clone, Default)]
+struct SegmentTimer {
+    start: Rc<RefCell<Option<Instant>>>,
+    segments: Rc<RefCell<Vec<metric::Segment>>>,
+}
+
+impl SessionEvents for SegmentTimer {
+    fn on_pre_prove_segment(&self, _segment: &risc0_zkvm::Segment) {
+        *self.start.borrow_mut() = Some(Instant::now());
+    }
+
+    fn on_post_prove_segment(&self, segment: &risc0_zkvm::Segment) {
+        let prove_millis = self
+            .start
+            .borrow_mut()
+            .take()
+            .map(|start| start.elapsed().as_millis());
+        self.segments.borrow_mut().push(metric::Segment {
+            cycles: Some(segment.cycles as u64),
+            po2: Some(segment.po2),
+            prove_millis,
+            ..Default::default()
+        });
+    }
+}
+
 pub fn measure<X>(
     metrics: &mut metric::Metrics,
     vm_args: VmArgs,
@@ -37,23 +139,45 @@ pub fn measure<X>(
     extra_input: Vec<u32>,
 ) -> Result<()>
 where
-    X: Clone + Runnable + serde::Serialize,
-    BenchmarkInput: From<X>,
+    X: Clone + Into<BenchmarkInput> + Runnable + serde::Serialize,
 {
     info!("-=-=-=-=-=- Measuring R0 -=-=-=-=-=-");
 
-    let input = BenchmarkInput::from(input);
+    let inner_inputs = input.inner();
+    let input: BenchmarkInput = input.into();
+
+    let _dev_mode = if vm_args.fake_proofs {
+        info!("Setting env vars ...");
+        Some(DevMode::set())
+    } else {
+        None
+    };
 
     info!("Creating prover ...");
     let prover = metric::measure(&mut metrics.prover_create_millis, || Ok(default_prover()))?;
+    // The steps are timed with the local prover server, other provers from `RISC0_PROVER`,
+    // such as bonsai or ipc, only time the whole of each phase.
+    let local = prover.get_name() == "local";
 
-    info!("Running executor ...");
-    {
+    let inner = if inner_inputs.is_empty() {
+        vec![]
+    } else {
+        info!("Inner proof workflow ...");
+        let inner = metric::measure(&mut metrics.inner_prove_millis, || {
+            prove_inner(prover.as_ref(), vm_args.segment_size, inner_inputs)
+        })?;
+        metrics.inner_proofs = Some(inner.len() as u64);
+        inner
+    };
+
+    let mut segments = if vm_args.runs(Phase::Exec) {
+        info!("Running executor ...");
         let mut env = ExecutorEnv::builder();
         env.write(&input).context("Write input for executor")?;
         for val in &extra_input {
             env.write(val).context("Write input for executor")?;
         }
+        add_inner(&mut env, &inner)?;
         env.segment_limit_po2(vm_args.segment_size);
         let env = env.build().context("Build env for executor")?;
 
@@ -73,16 +197,38 @@ where
         );
 
         info!("  Reading guest output ...");
-        let output = session
+        let (output, cycles) = session
             .journal
-            .decode::<X::Output>()
+            .decode::<(X::Output, Cycles)>()
             .context("Decode executor journal")?;
         metrics.exec_output = Some(format!("{:?}", output));
+        metrics.exec_decode_cycles = cycles.decode;
+        metrics.exec_compute_cycles = cycles.compute;
+        metrics.exec_commit_cycles = cycles.commit;
+
+        // Replaced by the proved segments, unless the dev mode prover skips them
+        session
+            .segments
+            .iter()
+            .map(|segment| metric::Segment {
+                cycles: Some(segment.cycles as u64),
+                po2: Some(segment.po2),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+
+    if !vm_args.runs(Phase::Prove) {
+        info!("Done");
+        return Ok(());
     }
 
     info!("Segmented proof workflow ...");
     let proof = {
         info!("  Proving ...");
+        let timer = SegmentTimer::default();
         let proof_info = {
             let mut env = ExecutorEnv::builder();
             env.write(&input)
@@ -90,27 +236,38 @@ where
             for val in &extra_input {
                 env.write(val).context("Write input for segmented proof")?;
             }
+            add_inner(&mut env, &inner)?;
             env.segment_limit_po2(vm_args.segment_size);
             let env = env.build().context("Build env for segmented proof")?;
 
-            let proof_info = metric::measure(&mut metrics.segmented_prove_millis, || {
-                prover.prove(env, GUEST_R0_ELF).context("Segmented prove")
-            })?;
-
-            proof_info
+            // Same steps as `prove`, with a hook on the session to time each segment
+            metric::measure(&mut metrics.segmented_prove_millis, || {
+                if !local {
+                    return prover.prove(env, GUEST_R0_ELF).context("Segmented prove");
+                }
+                let mut session = ExecutorImpl::from_elf(env, GUEST_R0_ELF)
+                    .context("Create executor for segmented proof")?
+                    .run()
+                    .context("Run executor for segmented proof")?;
+                session.add_hook(timer.clone());
+                get_prover_server(&ProverOpts::default())
+                    .context("Create prover server")?
+                    .prove_session(&VerifierContext::default(), &session)
+                    .context("Segmented prove")
+            })?
         };
 
         let receipt = proof_info.receipt;
 
         info!("  Reading proof metadata ...");
-        metrics.segmented_poof_segments = Some(proof_info.stats.segments as u64);
+        metrics.segmented_proof_segments = Some(proof_info.stats.segments as u64);
         metrics.segmented_proof_kind = Some(proof_kind(&receipt));
 
         info!("  Reading guest output ...");
         {
-            let output = receipt
+            let (output, _) = receipt
                 .journal
-                .decode::<X::Output>()
+                .decode::<(X::Output, Cycles)>()
                 .context("Decode segmented journal")?;
             metrics.segmented_proof_output = Some(format!("{:?}", output));
         }
@@ -133,19 +290,88 @@ where
             let inner_receipt_bytes =
                 bincode::serialize(&receipt.inner).context("Serialize segmented inner receipt")?;
             metrics.segmented_proof_bytes = Some(inner_receipt_bytes.len() as u128);
+
+            let proved = timer.segments.take();
+            if !proved.is_empty() {
+                segments = proved;
+            }
+
+            // Measure segment receipt sizes
+            if let risc0_zkvm::InnerReceipt::Composite(composite) = &receipt.inner {
+                // Empty if neither the executor nor the hook recorded the segments
+                segments.resize_with(composite.segments.len(), Default::default);
+                for (segment, receipt) in segments.iter_mut().zip(&composite.segments) {
+                    let receipt_bytes =
+                        bincode::serialize(receipt).context("Serialize segment receipt")?;
+                    segment.proof_bytes = Some(receipt_bytes.len() as u128);
+                }
+            }
+            metrics.segmented_segments = Some(serde_json::to_string(&segments)?);
         }
 
         receipt
     };
 
+    if !vm_args.runs(Phase::Reduce) {
+        info!("Done");
+        return Ok(());
+    }
+
     info!("Reduced proof workflow ...");
-    let _proof = {
+    let proof = {
         info!("  Proving ...");
-        let receipt = metric::measure(&mut metrics.reduced_prove_millis, || {
-            prover
-                .compress(&ProverOpts::succinct(), &proof)
-                .context("Reduced prove")
-        })?;
+        let receipt = match &proof.inner {
+            // Same steps as `compress(&ProverOpts::succinct(), ..)`, to time each of them
+            InnerReceipt::Composite(composite) if local => {
+                let receipt = metric::measure(&mut metrics.reduced_prove_millis, || {
+                    let server = get_prover_server(&ProverOpts::succinct())
+                        .context("Create prover server")?;
+
+                    let mut rollup = None;
+                    for segment in &composite.segments {
+                        let lifted = metric::measure_add(&mut metrics.reduced_lift_millis, || {
+                            server.lift(segment).context("Lift segment")
+                        })?;
+                        rollup = Some(match rollup {
+                            None => lifted,
+                            Some(rollup) => {
+                                metric::measure_add(&mut metrics.reduced_join_millis, || {
+                                    server.join(&rollup, &lifted).context("Join segments")
+                                })?
+                            }
+                        });
+                    }
+                    let mut rollup = rollup.context("Segmented proof has no segments")?;
+
+                    for assumption in &composite.assumptions {
+                        let assumption = match assumption {
+                            InnerReceipt::Succinct(assumption) => assumption,
+                            _ => bail!("Assumption is not a succinct receipt"),
+                        };
+                        rollup = metric::measure_add(&mut metrics.reduced_resolve_millis, || {
+                            server
+                                .resolve(&rollup, assumption)
+                                .context("Resolve assumption")
+                        })?;
+                    }
+
+                    Ok(Receipt::new(
+                        InnerReceipt::Succinct(rollup),
+                        proof.journal.bytes.clone(),
+                    ))
+                })?;
+                metrics.reduced_lifts = Some(composite.segments.len() as u64);
+                metrics.reduced_joins = Some(composite.segments.len() as u64 - 1);
+                metrics.reduced_resolves = Some(composite.assumptions.len() as u64);
+                receipt
+            }
+            // Fake receipts from the dev mode prover, and other provers
+            _ => metric::measure(&mut metrics.reduced_prove_millis, || {
+                prover
+                    .compress(&ProverOpts::succinct(), &proof)
+                    .context("Reduced prove")
+            })?,
+        };
 
         info!("  Reading proof metadata ...");
         metrics.reduced_proof_kind = Some(proof_kind(&receipt));
@@ -170,6 +396,69 @@ where
         receipt
     };
 
+    if !vm_args.runs(Phase::Wrap) {
+        info!("Done");
+        return Ok(());
+    }
+
+    info!("Wrapped proof workflow ...");
+    {
+        // The succinct receipt is wrapped as it is, there is no smaller STARK in between.
+        metrics.mark_unsupported("wrapped_shrink_millis");
+
+        info!("  Proving ...");
+        let receipt = match &proof.inner {
+            // Same steps as `compress(&ProverOpts::compact(), ..)`, to time each of them
+            InnerReceipt::Succinct(succinct) if local => {
+                metric::measure(&mut metrics.wrapped_prove_millis, || {
+                    let server = get_prover_server(&ProverOpts::compact())
+                        .context("Create prover server")?;
+
+                    let p254 = metric::measure(&mut metrics.wrapped_stark_bn254_millis, || {
+                        server.identity_p254(succinct).context("Identity p254")
+                    })?;
+
+                    // The STARK to Groth16 step runs the gnark prover in Docker.
+                    let seal = metric::measure(&mut metrics.wrapped_snark_millis, || {
+                        stark_to_snark(&p254.get_seal_bytes())
+                            .context("Wrapped prove, is Docker running?")
+                    })?;
+
+                    Ok(Receipt::new(
+                        InnerReceipt::Compact(CompactReceipt {
+                            seal: seal.to_vec(),
+                            claim: p254.claim,
+                            verifier_parameters: CompactReceiptVerifierParameters::default()
+                                .digest(),
+                        }),
+                        proof.journal.bytes.clone(),
+                    ))
+                })?
+            }
+            // Fake receipts from the dev mode prover, and other provers
+            _ => metric::measure(&mut metrics.wrapped_prove_millis, || {
+                prover
+                    .compress(&ProverOpts::compact(), &proof)
+                    .context("Wrapped prove")
+            })?,
+        };
+
+        info!("  Reading proof metadata ...");
+        metrics.wrapped_proof_kind = Some(proof_kind(&receipt));
+
+        info!("  Verifying proof ...");
+        metric::measure(&mut metrics.wrapped_verify_millis, || {
+            receipt.verify(GUEST_R0_ID).context("Verify wrapped proof")
+        })?;
+
+        info!("  Measuring proof size ...");
+        {
+            let inner_receipt_bytes =
+                bincode::serialize(&receipt.inner).context("Serialize wrapped inner receipt")?;
+            metrics.wrapped_proof_bytes = Some(inner_receipt_bytes.len() as u128);
+        }
+    }
+
     info!("Done");
 
     Ok(())
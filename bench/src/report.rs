@@ -0,0 +1,385 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison tables of stored measurements, printed by the `report` command.
+//!
+//! Measurements are grouped by machine_tag, benchmark_name, benchmark_size, benchmark_params, vm,
+//! [build] and segment_size, and each group shows the mean of its runs. There is one table per machine
+//! and benchmark, with an `r0/sp1` row after each size and params that have both VMs, comparing
+//! the best group of each.
+
+use std::{collections::BTreeMap, io};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use common::BenchmarkParam;
+use serde_json::{Map, Value};
+
+use crate::{
+    metric::{Measurement, Meta},
+    schema,
+};
+
+/// Metrics shown in the tables.
+pub const COLUMNS: &[&str] = &[
+    "exec_millis",
+    "exec_user_cycles",
+    "segmented_prove_millis",
+    "reduced_prove_millis",
+    "wrapped_prove_millis",
+    "segmented_proof_bytes",
+    "reduced_proof_bytes",
+    "wrapped_proof_bytes",
+];
+
+const GROUP_COLUMNS: &[&str] = &[
+    "benchmark_size",
+    "benchmark_params",
+    "vm",
+    "build",
+    "segment_size",
+    "runs",
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Aligned plain text tables
+    Table,
+    Markdown,
+    /// One CSV table with machine_tag and benchmark_name columns
+    Csv,
+}
+
+/// Guest build of the measured VM: the start of the hash of its ELF, which changes with the
+/// guest sources and the VM version.
+pub fn build(meta: &Meta) -> String {
+    let elf_sha256 = match meta.vm.as_str() {
+        "sp1" => &meta.sp1_elf_sha256,
+        _ => &meta.r0_elf_sha256,
+    };
+    elf_sha256.chars().take(8).collect()
+}
+
+/// Fields of the measurement by column name, as used in the CSV header, its [build], and its
+/// benchmark params by key, e.g. `accounts`.
+pub fn values(measurement: &Measurement) -> Result<Map<String, Value>> {
+    let Measurement(meta, metrics) = measurement;
+    let mut values = Map::new();
+    for val in [
+        serde_json::to_value(meta).context("Serialize meta")?,
+        serde_json::to_value(metrics).context("Serialize metrics")?,
+    ] {
+        if let Value::Object(fields) = val {
+            values.extend(fields);
+        }
+    }
+    values.insert("build".into(), build(meta).into());
+
+    // Rows from before the params were stored have none
+    let params: Map<String, Value> =
+        serde_json::from_str(&meta.benchmark_params).unwrap_or_default();
+    for (key, val) in params {
+        values.entry(key).or_insert(val);
+    }
+    Ok(values)
+}
+
+/// Numeric value of a column, `true` counting as 1.
+pub fn number(values: &Map<String, Value>, column: &str) -> Option<f64> {
+    match values.get(column)? {
+        Value::Number(n) => n.as_f64(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+/// Text of a column, empty when it has no value.
+pub fn text(values: &Map<String, Value>, column: &str) -> String {
+    match values.get(column) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(val) => val.to_string(),
+    }
+}
+
+/// Fails unless the column is in the CSV header, is the [build] or a param of a benchmark.
+pub fn check_column(column: &str) -> Result<()> {
+    if column == "build" || schema::header()?.iter().any(|name| name == column) {
+        return Ok(());
+    }
+
+    let benchmarks = BenchmarkParam::augment_subcommands(clap::Command::new("benchmark"));
+    let is_param = benchmarks
+        .get_subcommands()
+        .flat_map(|benchmark| benchmark.get_arguments())
+        .any(|arg| arg.get_id() == column);
+    if !is_param {
+        bail!("Unknown column {}", column);
+    }
+    Ok(())
+}
+
+/// Selects the measurements of `report` and `plot`.
+#[derive(Args, Clone, Debug, Default)]
+pub struct Filter {
+    /// Only measurements whose column or benchmark param has the value, e.g. `--where=vm=r0` or
+    /// `--where=accounts=1000`, can be repeated
+    #[arg(long = "where", require_equals = true, value_parser = parse_condition)]
+    pub conditions: Vec<(String, String)>,
+
+    /// Include measurements taken with `--fake-proofs`
+    #[arg(long)]
+    pub include_fake_proofs: bool,
+}
+
+fn parse_condition(arg: &str) -> Result<(String, String)> {
+    let Some((column, val)) = arg.split_once('=') else {
+        bail!("Expected COLUMN=VALUE");
+    };
+    check_column(column)?;
+    Ok((column.into(), val.into()))
+}
+
+impl Filter {
+    pub fn apply(&self, measurements: Vec<Measurement>) -> Result<Vec<Measurement>> {
+        let mut selected = vec![];
+        for measurement in measurements {
+            if measurement.0.fake_proofs && !self.include_fake_proofs {
+                continue;
+            }
+            let values = values(&measurement)?;
+            if self
+                .conditions
+                .iter()
+                .all(|(column, val)| text(&values, column) == *val)
+            {
+                selected.push(measurement);
+            }
+        }
+        Ok(selected)
+    }
+}
+
+type TableKey = (String, String);
+type GroupKey = (u32, String, String, String, u32);
+
+/// Mean of each column over the runs of a group.
+struct Group {
+    runs: usize,
+    means: Vec<Option<f64>>,
+}
+
+type Tables = BTreeMap<TableKey, BTreeMap<GroupKey, Group>>;
+
+fn tables(measurements: &[Measurement]) -> Result<Tables> {
+    let mut runs: BTreeMap<TableKey, BTreeMap<GroupKey, Vec<Map<String, Value>>>> = BTreeMap::new();
+    for measurement in measurements {
+        let Measurement(meta, _) = measurement;
+        runs.entry((meta.machine_tag.clone(), meta.benchmark_name.clone()))
+            .or_default()
+            .entry((
+                meta.benchmark_size,
+                meta.benchmark_params.clone(),
+                meta.vm.clone(),
+                build(meta),
+                meta.segment_size,
+            ))
+            .or_default()
+            .push(values(measurement)?);
+    }
+
+    Ok(runs
+        .into_iter()
+        .map(|(table, groups)| {
+            let groups = groups
+                .into_iter()
+                .map(|(group, runs)| {
+                    let means = COLUMNS
+                        .iter()
+                        .map(|column| {
+                            // Runs that failed before the metric was measured are left out
+                            let vals: Vec<f64> =
+                                runs.iter().filter_map(|run| number(run, column)).collect();
+                            (!vals.is_empty()).then(|| vals.iter().sum::<f64>() / vals.len() as f64)
+                        })
+                        .collect();
+                    let runs = runs.len();
+                    (group, Group { runs, means })
+                })
+                .collect();
+            (table, groups)
+        })
+        .collect())
+}
+
+/// Rows of a table: the groups, then the `r0/sp1` ratio of each size and params.
+fn rows(groups: &BTreeMap<GroupKey, Group>) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    // Other params, like the accounts of mpt, change the work done at the same size
+    let mut sizes: Vec<(u32, &String)> = groups
+        .keys()
+        .map(|(size, params, _, _, _)| (*size, params))
+        .collect();
+    sizes.dedup();
+
+    for (size, params) in sizes {
+        let groups: Vec<(&GroupKey, &Group)> = groups
+            .iter()
+            .filter(|((group_size, group_params, _, _, _), _)| {
+                *group_size == size && group_params == params
+            })
+            .collect();
+
+        for ((size, params, vm, build, segment_size), group) in &groups {
+            rows.push(
+                [
+                    size.to_string(),
+                    params.clone(),
+                    vm.clone(),
+                    build.clone(),
+                    segment_size.to_string(),
+                    group.runs.to_string(),
+                ]
+                .into_iter()
+                .chain(
+                    group
+                        .means
+                        .iter()
+                        .map(|mean| mean.map(|mean| format!("{:.0}", mean)).unwrap_or_default()),
+                )
+                .collect(),
+            );
+        }
+
+        // Lower is better for every column, so compare the best build and segment size of each VM
+        let best = |vm: &str, i: usize| {
+            groups
+                .iter()
+                .filter(|((_, _, group_vm, _, _), _)| group_vm == vm)
+                .filter_map(|(_, group)| group.means[i])
+                .min_by(f64::total_cmp)
+        };
+        let has_vm = |vm: &str| {
+            groups
+                .iter()
+                .any(|((_, _, group_vm, _, _), _)| group_vm == vm)
+        };
+        if has_vm("r0") && has_vm("sp1") {
+            rows.push(
+                [
+                    size.to_string(),
+                    params.clone(),
+                    "r0/sp1".into(),
+                    "".into(),
+                    "".into(),
+                    "".into(),
+                ]
+                .into_iter()
+                .chain(
+                    (0..COLUMNS.len()).map(|i| match (best("r0", i), best("sp1", i)) {
+                        (Some(r0), Some(sp1)) if sp1 != 0.0 => format!("{:.2}", r0 / sp1),
+                        _ => "".into(),
+                    }),
+                )
+                .collect(),
+            );
+        }
+    }
+
+    rows
+}
+
+fn header() -> Vec<String> {
+    GROUP_COLUMNS
+        .iter()
+        .chain(COLUMNS)
+        .map(|column| column.to_string())
+        .collect()
+}
+
+/// Writes the report of the measurements in the given format.
+pub fn write<W: io::Write>(wtr: W, measurements: &[Measurement], format: Format) -> Result<()> {
+    let tables = tables(measurements)?;
+    match format {
+        Format::Table | Format::Markdown => write_text(wtr, &tables, format),
+        Format::Csv => write_csv(wtr, &tables),
+    }
+}
+
+fn write_text<W: io::Write>(mut wtr: W, tables: &Tables, format: Format) -> Result<()> {
+    let header = header();
+
+    for ((machine_tag, benchmark_name), groups) in tables {
+        let rows = rows(groups);
+
+        if format == Format::Markdown {
+            writeln!(wtr, "### {} {}", machine_tag, benchmark_name)?;
+            writeln!(wtr)?;
+            writeln!(wtr, "| {} |", header.join(" | "))?;
+            writeln!(wtr, "|{}", "---:|".repeat(header.len()))?;
+            for row in &rows {
+                writeln!(wtr, "| {} |", row.join(" | "))?;
+            }
+        } else {
+            let widths: Vec<usize> = (0..header.len())
+                .map(|i| {
+                    rows.iter()
+                        .map(|row| row[i].len())
+                        .chain([header[i].len()])
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect();
+            let line = |row: &[String]| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+            writeln!(wtr, "{} {}", machine_tag, benchmark_name)?;
+            writeln!(wtr)?;
+            writeln!(wtr, "{}", line(&header))?;
+            writeln!(wtr, "{}", line(&separator))?;
+            for row in &rows {
+                writeln!(wtr, "{}", line(row))?;
+            }
+        }
+        writeln!(wtr)?;
+    }
+
+    Ok(())
+}
+
+fn write_csv<W: io::Write>(wtr: W, tables: &Tables) -> Result<()> {
+    let mut wtr = csv::Writer::from_writer(wtr);
+    wtr.write_record(
+        ["machine_tag".to_string(), "benchmark_name".to_string()]
+            .into_iter()
+            .chain(header()),
+    )
+    .context("Write CSV header")?;
+
+    for ((machine_tag, benchmark_name), groups) in tables {
+        for row in rows(groups) {
+            wtr.write_record([machine_tag, benchmark_name].into_iter().chain(&row))
+                .context("Write CSV row")?;
+        }
+    }
+
+    wtr.flush().context("Flush CSV")?;
+    Ok(())
+}
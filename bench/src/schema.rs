@@ -0,0 +1,147 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Versioned CSV schema of [Measurement] rows.
+//!
+//! Every row records the `schema_version` it was written with, and the header row names the
+//! columns. Rows of older versions are upgraded by column name when they are read.
+
+use std::{collections::HashMap, io};
+
+use anyhow::{ensure, Context, Result};
+use csv::StringRecord;
+
+use crate::metric::Measurement;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Columns renamed by a schema version: `(version, old name, new name)`.
+const RENAMED: &[(u32, &str, &str)] = &[(1, "segmented_poof_segments", "segmented_proof_segments")];
+
+/// Values of columns that are not optional, for rows written before the column existed.
+const DEFAULTS: &[(&str, &str)] = &[("benchmark_params", "{}"), ("fake_proofs", "false")];
+
+/// A row that could not be read, with the reason.
+pub struct Rejected {
+    pub line: u64,
+    pub record: StringRecord,
+    pub error: anyhow::Error,
+}
+
+/// Column names of the current schema.
+pub fn header() -> Result<StringRecord> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.serialize(Measurement::default())
+        .context("Serialize header")?;
+    let data = wtr.into_inner().context("Flush header")?;
+
+    let mut rdr = csv::Reader::from_reader(data.as_slice());
+    Ok(rdr.headers().context("Read header")?.clone())
+}
+
+/// Reads rows of any schema version, upgrading them to the current one.
+pub fn read<R: io::Read>(rdr: R) -> Result<(Vec<Measurement>, Vec<Rejected>)> {
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(rdr);
+    let columns = rdr.headers().context("Read CSV header")?.clone();
+    let header = header()?;
+
+    let mut measurements = vec![];
+    let mut rejected = vec![];
+    for record in rdr.records() {
+        let record = record.context("Read CSV row")?;
+        let line = record.position().map(|pos| pos.line()).unwrap_or_default();
+
+        // Upgraded rows are in the column order of the current header
+        let measurement = upgrade(&columns, &record, &header).and_then(|row| {
+            row.deserialize::<Measurement>(None)
+                .context("Deserialize row")
+        });
+        match measurement {
+            Ok(measurement) => measurements.push(measurement),
+            Err(error) => rejected.push(Rejected {
+                line,
+                record,
+                error,
+            }),
+        }
+    }
+
+    Ok((measurements, rejected))
+}
+
+/// Writes the header row and every measurement with the current schema.
+pub fn write<W: io::Write>(wtr: W, measurements: &[Measurement]) -> Result<()> {
+    let mut wtr = csv::Writer::from_writer(wtr);
+    for measurement in measurements {
+        let mut measurement = measurement.clone();
+        measurement.0.schema_version = SCHEMA_VERSION;
+        wtr.serialize(measurement)
+            .context("Serialize metric into CSV")?;
+    }
+    wtr.flush().context("Flush CSV")?;
+    Ok(())
+}
+
+/// Maps a row with the given columns to the columns of the current schema.
+fn upgrade(
+    columns: &StringRecord,
+    record: &StringRecord,
+    header: &StringRecord,
+) -> Result<StringRecord> {
+    ensure!(
+        columns.len() == record.len(),
+        "Row has {} columns, the header {}",
+        record.len(),
+        columns.len()
+    );
+    let mut row: HashMap<&str, &str> = columns.iter().zip(record.iter()).collect();
+
+    // Rows from before the schema was versioned have no `schema_version` column.
+    let version = match row.get("schema_version") {
+        Some(version) => version.parse().context("Parse schema_version")?,
+        None => 0,
+    };
+    ensure!(
+        version <= SCHEMA_VERSION,
+        "Schema version {} is newer than {}, update bench",
+        version,
+        SCHEMA_VERSION
+    );
+
+    for (since, old, new) in RENAMED {
+        if version < *since {
+            if let Some(val) = row.remove(old) {
+                row.insert(new, val);
+            }
+        }
+    }
+
+    let schema_version = SCHEMA_VERSION.to_string();
+    row.insert("schema_version", &schema_version);
+
+    Ok(header
+        .iter()
+        .map(|column| {
+            row.get(column)
+                .copied()
+                .or_else(|| {
+                    DEFAULTS
+                        .iter()
+                        .find(|(name, _)| *name == column)
+                        .map(|(_, val)| *val)
+                })
+                .unwrap_or_default()
+        })
+        .collect())
+}
@@ -12,28 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use common::*;
-use sp1_prover::{utils::get_cycles, SP1Prover, SP1Stdin};
+use sp1_prover::{
+    install::try_install_plonk_bn254_artifacts, utils::get_cycles, HashableKey, SP1Prover, SP1Stdin,
+};
 use tracing::info;
 
-use crate::{metric, VmArgs};
+use crate::{cli::Phase, cycle_tracker, metric, VmArgs};
 
-const ELF: &[u8] = include_bytes!("../../guest-sp1/elf/riscv32im-succinct-zkvm-elf");
+const ELF: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/riscv32im-succinct-zkvm-elf"));
+
+pub fn elf_sha256() -> String {
+    metric::hex(&sha256(ELF))
+}
 
 pub fn measure<X>(
+    meta: &mut metric::Meta,
     metrics: &mut metric::Metrics,
     vm_args: VmArgs,
     input: X,
     extra_input: Vec<u32>,
 ) -> Result<()>
 where
-    X: Clone + Runnable + serde::Serialize,
-    BenchmarkInput: From<X>,
+    X: Clone + Into<BenchmarkInput> + Runnable + serde::Serialize,
 {
     info!("-=-=-=-=-=- Measuring SP1 -=-=-=-=-=-");
 
-    let input = BenchmarkInput::from(input);
+    let inner_inputs = input.inner();
+    let input: BenchmarkInput = input.into();
 
     info!("Setting env vars ...");
     {
@@ -52,6 +59,39 @@ where
         std::env::set_var("SHARD_SIZE", format!("{}", 1 << vm_args.segment_size));
     }
 
+    info!("Creating prover ...");
+    let (prover, pk, vk) = metric::measure(&mut metrics.prover_create_millis, || {
+        let prover = SP1Prover::new();
+        let (pk, vk) = prover.setup(ELF);
+        Ok((prover, pk, vk))
+    })?;
+
+    meta.sp1_vk_hash = Some(vk.bytes32());
+    info!("  Verifying key hash: {}", vk.bytes32());
+
+    let inner = if inner_inputs.is_empty() {
+        vec![]
+    } else if vm_args.fake_proofs {
+        // The guest checks the inner proofs against their verifying key even when executed.
+        bail!("The inner proofs of this benchmark cannot be faked on sp1");
+    } else {
+        info!("Inner proof workflow ...");
+        let inner = metric::measure(&mut metrics.inner_prove_millis, || {
+            Ok(inner_inputs
+                .into_iter()
+                .map(|input| {
+                    let mut stdin = SP1Stdin::new();
+                    stdin.write(&input);
+                    let proof = prover.prove_core(&pk, &stdin);
+                    let public_values = proof.public_values.as_slice().to_vec();
+                    (public_values, prover.compress(&vk, proof, vec![]))
+                })
+                .collect::<Vec<_>>())
+        })?;
+        metrics.inner_proofs = Some(inner.len() as u64);
+        inner
+    };
+
     info!("Setting up stdin ...");
     let stdin = {
         let mut stdin = SP1Stdin::new();
@@ -60,21 +100,28 @@ where
             stdin.write(val);
         }
 
+        // Inner proofs of the benchmark, verified by the guest against their public
+        // values and deferred to the reduced proof.
+        if !inner.is_empty() {
+            let journals: Vec<&Vec<u8>> = inner.iter().map(|(journal, _)| journal).collect();
+            stdin.write(&vk.hash_u32());
+            stdin.write(&journals);
+            for (_, proof) in &inner {
+                stdin.write_proof(proof.proof.clone(), vk.vk.clone());
+            }
+        }
+
         stdin
     };
 
-    info!("Creating prover ...");
-    let (prover, pk, vk) = metric::measure(&mut metrics.prover_create_millis, || {
-        let prover = SP1Prover::new();
-        let (pk, vk) = prover.setup(ELF);
-        Ok((prover, pk, vk))
-    })?;
-
-    info!("Running executor ...");
-    {
-        let mut session = metric::measure(&mut metrics.exec_millis, || {
-            Ok(SP1Prover::execute(ELF, &stdin))
-        })?;
+    if vm_args.runs(Phase::Exec) {
+        info!("Running executor ...");
+        let (session, regions) = cycle_tracker::collect(|| {
+            metric::measure(&mut metrics.exec_millis, || {
+                Ok(SP1Prover::execute(ELF, &stdin))
+            })
+        });
+        let mut session = session?;
 
         info!("  Reading session metadata ...");
         metrics.exec_user_cycles = Some(get_cycles(ELF, &stdin));
@@ -82,6 +129,26 @@ where
         info!("  Reading guest output ...");
         let output = session.read::<X::Output>();
         metrics.exec_output = Some(format!("{:?}", output));
+
+        // SP1 has no in-guest cycle counter, so the committed cycles are empty. The runtime
+        // reports the regions the guest marks around each phase instead.
+        metrics.exec_decode_cycles = regions.get("decode").copied();
+        metrics.exec_compute_cycles = regions.get("compute").copied();
+        metrics.exec_commit_cycles = regions.get("commit").copied();
+    }
+
+    // The pinned sp1-prover has no mock prover, so there are no fake proofs to measure.
+    if vm_args.fake_proofs {
+        for phase in [Phase::Prove, Phase::Reduce, Phase::Wrap] {
+            if vm_args.runs(phase) {
+                metrics.mark_unsupported(&phase.to_string());
+            }
+        }
+    }
+
+    if !vm_args.runs(Phase::Prove) || vm_args.fake_proofs {
+        info!("Done");
+        return Ok(());
     }
 
     info!("Segmented proof workflow ...");
@@ -92,7 +159,7 @@ where
         })?;
 
         info!("  Reading proof metadata ...");
-        metrics.segmented_poof_segments = Some(proof.proof.0.len() as u64);
+        metrics.segmented_proof_segments = Some(proof.proof.0.len() as u64);
         metrics.segmented_proof_kind = Some("SP1CoreProofData".into());
 
         info!("  Reading guest output ...");
@@ -117,29 +184,69 @@ where
             let proof_bytes =
                 bincode::serialize(&proof.proof).context("Serialize segmented inner proof")?;
             metrics.segmented_proof_bytes = Some(proof_bytes.len() as u128);
+
+            // Measure shard proof sizes, and the padded CPU trace height as the po2
+            let mut segments = vec![];
+            for shard in &proof.proof.0 {
+                let shard_bytes = bincode::serialize(shard).context("Serialize shard proof")?;
+                segments.push(metric::Segment {
+                    po2: Some(shard.log_degree_cpu() as u32),
+                    proof_bytes: Some(shard_bytes.len() as u128),
+                    ..Default::default()
+                });
+            }
+            metrics.segmented_segments = Some(serde_json::to_string(&segments)?);
+
+            // `prove_core` proves the shards in parallel batches without reporting progress, so
+            // a shard has no prove time of its own. The proofs only keep the padded trace
+            // height, not the cycles executed in the shard.
+            metrics.mark_unsupported("segmented_segments.prove_millis");
+            metrics.mark_unsupported("segmented_segments.cycles");
         }
 
         proof
     };
 
+    if !vm_args.runs(Phase::Reduce) {
+        info!("Done");
+        return Ok(());
+    }
+
+    let public_values = proof.public_values.clone();
+
     info!("Reduced proof workflow ...");
-    {
+    let proof = {
         info!("  Proving ...");
         let proof = metric::measure(&mut metrics.reduced_prove_millis, || {
-            Ok(prover.compress(&vk, proof, vec![]))
+            let deferred = inner.iter().map(|(_, proof)| proof.proof.clone()).collect();
+            Ok(prover.compress(&vk, proof, deferred))
         })?;
 
         info!("  Reading proof metadata ...");
         metrics.reduced_proof_kind = Some("SP1ReduceProof<BabyBearPoseidon2>".into());
 
-        // Not supported!
-        //
-        // info!("  Verifying proof ...");
-        // metric::measure(&mut metrics.reduced_verify_millis, || {
-        //     prover
-        //         .verify_compressed(&proof, &vk)
-        //         .context("Reduced verify")
-        // })?;
+        // `compress` runs the recursion as one pipeline, without a method or progress report
+        // per step of the pinned sp1-prover:
+        // - lift: the core shards are verified in batches by the recursion program, and those
+        //   batches are proven in parallel with the deferred ones;
+        // - join: the batch proofs are reduced in a tree, each layer started as soon as the
+        //   proofs below it are done, so it overlaps with the lift;
+        // - resolve: the inner proofs are verified by their own batches, in the same parallel
+        //   loop as the lift, instead of resolving assumptions afterwards like r0.
+        for metric in [
+            "reduced_lift_millis",
+            "reduced_join_millis",
+            "reduced_resolve_millis",
+        ] {
+            metrics.mark_unsupported(metric);
+        }
+
+        info!("  Verifying ...");
+        metric::measure(&mut metrics.reduced_verify_millis, || {
+            prover
+                .verify_compressed(&proof, &vk)
+                .context("Reduced verify")
+        })?;
 
         info!("  Measuring proof size ...");
         {
@@ -153,9 +260,67 @@ where
                 bincode::serialize(&proof.proof).context("Serialize reduced inner proof")?;
             metrics.reduced_proof_bytes = Some(proof_bytes.len() as u128);
         }
+
+        proof
+    };
+
+    if !vm_args.runs(Phase::Wrap) {
+        info!("Done");
+        return Ok(());
+    }
+
+    info!("Wrapped proof workflow ...");
+    {
+        // The gnark tooling panics when it is missing, report that as an error instead.
+        info!("  Installing PLONK artifacts ...");
+        let artifacts_dir = catch_panic("Install PLONK artifacts", || {
+            try_install_plonk_bn254_artifacts()
+        })?;
+
+        info!("  Proving ...");
+        let proof = metric::measure(&mut metrics.wrapped_prove_millis, || {
+            let proof = metric::measure(&mut metrics.wrapped_shrink_millis, || {
+                catch_panic("Shrink", || prover.shrink(proof))
+            })?;
+            let proof = metric::measure(&mut metrics.wrapped_stark_bn254_millis, || {
+                catch_panic("Wrap BN254", || prover.wrap_bn254(proof))
+            })?;
+            metric::measure(&mut metrics.wrapped_snark_millis, || {
+                catch_panic("Wrap PLONK", || {
+                    prover.wrap_plonk_bn254(proof, &artifacts_dir)
+                })
+            })
+        })?;
+
+        info!("  Reading proof metadata ...");
+        metrics.wrapped_proof_kind = Some("PlonkBn254Proof".into());
+
+        info!("  Verifying proof ...");
+        metric::measure(&mut metrics.wrapped_verify_millis, || {
+            prover
+                .verify_plonk_bn254(&proof, &vk, &public_values, &artifacts_dir)
+                .context("Wrapped verify")
+        })?;
+
+        info!("  Measuring proof size ...");
+        {
+            let proof_bytes = bincode::serialize(&proof).context("Serialize wrapped proof")?;
+            metrics.wrapped_proof_bytes = Some(proof_bytes.len() as u128);
+        }
     }
 
     info!("Done");
 
     Ok(())
 }
+
+fn catch_panic<X>(what: &str, f: impl FnOnce() -> X) -> Result<X> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|err| {
+        let msg = err
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| err.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown panic");
+        anyhow::anyhow!("{} panicked: {}", what, msg)
+    })
+}
@@ -0,0 +1,172 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SQLite store for measurements, selected with `--store=sqlite`.
+//!
+//! [Meta] goes into the `experiments` table, [Metrics] into `measurements` and every
+//! [Segment] of `segmented_segments` into `segments`, all keyed by `experiment_id`. Columns are
+//! named after the struct fields and missing columns are added when the database is opened,
+//! so databases written by older versions keep working.
+
+use std::{collections::HashSet, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use rusqlite::{
+    params_from_iter, types::Value as SqlValue, Connection, Transaction, TransactionBehavior,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::metric::{Measurement, Meta, Metrics, Segment};
+
+pub fn open(path: &Path) -> Result<Connection> {
+    let mut conn = Connection::open(path).context("Open SQLite database")?;
+    // Parallel `bench` processes wait for each other
+    conn.busy_timeout(Duration::from_secs(60))?;
+
+    // Parallel first runs would otherwise add the same columns twice
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .context("Begin transaction")?;
+
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS experiments (
+            experiment_id TEXT PRIMARY KEY
+        );
+        CREATE TABLE IF NOT EXISTS measurements (
+            experiment_id TEXT PRIMARY KEY REFERENCES experiments (experiment_id)
+        );
+        CREATE TABLE IF NOT EXISTS segments (
+            experiment_id TEXT NOT NULL REFERENCES experiments (experiment_id),
+            segment INTEGER NOT NULL,
+            PRIMARY KEY (experiment_id, segment)
+        );",
+    )
+    .context("Create tables")?;
+
+    add_columns(&tx, "experiments", row(&Meta::default())?)?;
+    add_columns(&tx, "measurements", metrics_row(&Metrics::default())?)?;
+    add_columns(&tx, "segments", row(&Segment::default())?)?;
+
+    tx.commit().context("Commit transaction")?;
+    Ok(conn)
+}
+
+/// Inserts a measurement, replacing any earlier one of the same experiment.
+pub fn insert(conn: &mut Connection, measurement: &Measurement) -> Result<()> {
+    let Measurement(meta, metrics) = measurement;
+    let experiment_id = Value::from(meta.experiment_id.clone());
+
+    let tx = conn.transaction().context("Begin transaction")?;
+
+    insert_row(&tx, "experiments", row(meta)?)?;
+
+    let mut measurement = metrics_row(metrics)?;
+    measurement.insert("experiment_id".into(), experiment_id.clone());
+    insert_row(&tx, "measurements", measurement)?;
+
+    tx.execute(
+        "DELETE FROM segments WHERE experiment_id = ?1",
+        [&meta.experiment_id],
+    )
+    .context("Delete segments")?;
+    if let Some(segments) = &metrics.segmented_segments {
+        let segments: Vec<Segment> =
+            serde_json::from_str(segments).context("Parse segmented_segments")?;
+        for (i, segment) in segments.iter().enumerate() {
+            let mut segment = row(segment)?;
+            segment.insert("experiment_id".into(), experiment_id.clone());
+            segment.insert("segment".into(), Value::from(i));
+            insert_row(&tx, "segments", segment)?;
+        }
+    }
+
+    tx.commit().context("Commit transaction")?;
+    Ok(())
+}
+
+fn row<T: Serialize>(val: &T) -> Result<Map<String, Value>> {
+    match serde_json::to_value(val).context("Serialize row")? {
+        Value::Object(row) => Ok(row),
+        _ => unreachable!("rows serialize to a JSON object"),
+    }
+}
+
+/// Metrics without `segmented_segments`, which has its own table.
+fn metrics_row(metrics: &Metrics) -> Result<Map<String, Value>> {
+    let mut row = row(metrics)?;
+    row.remove("segmented_segments");
+    Ok(row)
+}
+
+/// Stores strings as text rather than JSON, so they compare equal to SQL string literals.
+///
+/// Integers above `i64::MAX` do not fit an SQLite integer, and are stored as text rather than
+/// as a lossy real.
+fn sql_value(val: Value) -> SqlValue {
+    match val {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(b.into()),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => SqlValue::Integer(n),
+            None if n.is_u64() => SqlValue::Text(n.to_string()),
+            None => n
+                .as_f64()
+                .map_or(SqlValue::Text(n.to_string()), SqlValue::Real),
+        },
+        Value::String(s) => SqlValue::Text(s),
+        val => SqlValue::Text(val.to_string()),
+    }
+}
+
+/// Quotes a table or column name, so that it is never read as SQL.
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn add_columns(conn: &Connection, table: &str, row: Map<String, Value>) -> Result<()> {
+    let columns = conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")?
+        .query_map([table], |row| row.get(0))?
+        .collect::<Result<HashSet<String>, _>>()
+        .context("Read table columns")?;
+
+    for column in row.keys().filter(|column| !columns.contains(*column)) {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {}", quote(table), quote(column)),
+            [],
+        )
+        .with_context(|| format!("Add column {}.{}", table, column))?;
+    }
+
+    Ok(())
+}
+
+fn insert_row(tx: &Transaction, table: &str, row: Map<String, Value>) -> Result<()> {
+    let columns: Vec<String> = row.keys().map(|column| quote(column)).collect();
+    let placeholders = vec!["?"; columns.len()].join(", ");
+
+    tx.execute(
+        &format!(
+            "INSERT OR REPLACE INTO {} ({}) VALUES ({})",
+            quote(table),
+            columns.join(", "),
+            placeholders
+        ),
+        params_from_iter(row.into_values().map(sql_value)),
+    )
+    .with_context(|| format!("Insert into {}", table))?;
+
+    Ok(())
+}
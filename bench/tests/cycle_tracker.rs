@@ -0,0 +1,44 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use bench::cycle_tracker::{self, CycleTracker};
+use tracing_subscriber::{prelude::*, Registry};
+
+#[test]
+fn region_cycles() {
+    let subscriber = Registry::default().with(CycleTracker);
+    let ((), cycles) = tracing::subscriber::with_default(subscriber, || {
+        // As logged by the SP1 runtime
+        cycle_tracker::collect(|| {
+            tracing::info!("┌╴decode");
+            tracing::info!("└╴1,234 cycles");
+            tracing::info!("┌╴compute");
+            tracing::info!("│ ┌╴inner");
+            tracing::info!("│ └╴5 cycles");
+            tracing::info!("└╴1,000,000 cycles");
+            tracing::info!("stdout: 42");
+        })
+    });
+
+    assert_eq!(
+        cycles,
+        HashMap::from([
+            ("decode".to_string(), 1234),
+            ("inner".to_string(), 5),
+            ("compute".to_string(), 1_000_000),
+        ])
+    );
+}
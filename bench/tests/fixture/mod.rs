@@ -0,0 +1,92 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Measurements for the tests that do not run the `measure` command.
+
+// Each test crate only uses some of the setters
+#![allow(dead_code)]
+
+use bench::metric::{Measurement, Meta, Segment};
+
+/// Starts a measurement of `fib 100` on r0 with segment size 20, on the `test` machine.
+pub fn measurement() -> MeasurementBuilder {
+    let meta = Meta {
+        machine_tag: "test".into(),
+        ..Default::default()
+    };
+    MeasurementBuilder(Measurement(meta, Default::default()))
+        .benchmark("fib", 100, r#"{"n":100}"#)
+        .vm("r0")
+        .segment_size(20)
+}
+
+pub struct MeasurementBuilder(Measurement);
+
+impl MeasurementBuilder {
+    pub fn experiment_id(mut self, experiment_id: &str) -> Self {
+        self.0 .0.experiment_id = experiment_id.into();
+        self
+    }
+
+    pub fn datetime(mut self, datetime: &str) -> Self {
+        self.0 .0.datetime = datetime.into();
+        self
+    }
+
+    pub fn benchmark(mut self, name: &str, size: u32, params: &str) -> Self {
+        let meta = &mut self.0 .0;
+        meta.benchmark_name = name.into();
+        meta.benchmark_size = size;
+        meta.benchmark_params = params.into();
+        self
+    }
+
+    pub fn vm(mut self, vm: &str) -> Self {
+        self.0 .0.vm = vm.into();
+        self
+    }
+
+    /// Guest ELF hash of both VMs, which tells apart their builds.
+    pub fn elf_sha256(mut self, elf_sha256: &str) -> Self {
+        self.0 .0.r0_elf_sha256 = elf_sha256.into();
+        self.0 .0.sp1_elf_sha256 = elf_sha256.into();
+        self
+    }
+
+    pub fn segment_size(mut self, segment_size: u32) -> Self {
+        self.0 .0.segment_size = segment_size;
+        self
+    }
+
+    pub fn exec_user_cycles(mut self, cycles: u64) -> Self {
+        self.0 .1.exec_user_cycles = Some(cycles);
+        self
+    }
+
+    pub fn prove_millis(mut self, prove_millis: u128) -> Self {
+        self.0 .1.segmented_prove_millis = Some(prove_millis);
+        self
+    }
+
+    /// Empty entries for the given number of segments.
+    pub fn segments(mut self, segments: usize) -> Self {
+        self.0 .1.segmented_segments =
+            Some(serde_json::to_string(&vec![Segment::default(); segments]).unwrap());
+        self
+    }
+
+    pub fn build(self) -> Measurement {
+        self.0
+    }
+}
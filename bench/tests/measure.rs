@@ -0,0 +1,308 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runs the `measure` command end to end with fake proofs or without proving, which still
+//! executes the guests.
+
+use bench::{cli::Cli, metric::Measurement, schema};
+use clap::Parser;
+use common::{env::HostEnv, Benchmark, BenchmarkVisitor, Runnable};
+
+const MACHINE_TAG: &str = "test";
+
+/// Output of the benchmark run on the host, formatted like `exec_output`.
+struct HostOutput;
+
+impl BenchmarkVisitor for HostOutput {
+    type Output = String;
+
+    fn visit<B: Benchmark>(self, benchmark: &B) -> String {
+        let (input, extra_input) = benchmark.input();
+        let mut env = HostEnv::new();
+        for val in &extra_input {
+            env.write(val);
+        }
+        format!("{:?}", input.run(&mut env).unwrap())
+    }
+}
+
+struct Run {
+    datadir: tempfile::TempDir,
+    cli: Cli,
+    measurements: Vec<Measurement>,
+}
+
+fn measure(options: &[&str], benchmark: &[&str]) -> Run {
+    measure_vm("r0", options, benchmark)
+}
+
+fn measure_vm(vm: &str, options: &[&str], benchmark: &[&str]) -> Run {
+    let datadir = tempfile::tempdir().unwrap();
+    let args = [
+        "bench".to_string(),
+        format!("--datadir={}", datadir.path().display()),
+        "measure".into(),
+        "--segment-size=20".into(),
+    ]
+    .into_iter()
+    .chain(options.iter().map(|arg| arg.to_string()))
+    .chain([MACHINE_TAG.into(), vm.into()])
+    .chain(benchmark.iter().map(|arg| arg.to_string()));
+
+    let cli = Cli::try_parse_from(args).unwrap();
+    cli.run().unwrap();
+
+    let datadir_arg = format!("--datadir={}", datadir.path().display());
+    let merge = Cli::try_parse_from(["bench", &datadir_arg, "merge"]).unwrap();
+    merge.run().unwrap();
+
+    let csv_file = std::fs::File::open(cli.measurements_file_path()).unwrap();
+    let (measurements, rejected) = schema::read(csv_file).unwrap();
+    assert!(rejected.is_empty());
+
+    Run {
+        datadir,
+        cli,
+        measurements,
+    }
+}
+
+/// Checks a run with fake proofs.
+fn check(run: &Run) -> &Measurement {
+    assert_eq!(run.measurements.len(), 1);
+    let measurement = &run.measurements[0];
+    let Measurement(meta, metrics) = measurement;
+
+    assert_eq!(metrics.error_string, None);
+    assert!(meta.fake_proofs);
+    assert_eq!(meta.machine_tag, MACHINE_TAG);
+    assert_eq!(meta.vm, "r0");
+    assert!(metrics.exec_user_cycles.unwrap() > 0);
+    assert_eq!(metrics.reduced_proof_kind.as_deref(), Some("fake"));
+
+    let experiment_dir = run.datadir.path().join(MACHINE_TAG);
+    for extension in ["log", "csv"] {
+        let path = experiment_dir.join(format!("{}.{}", meta.experiment_id, extension));
+        assert!(path.is_file(), "missing {}", path.display());
+    }
+
+    // Only this experiment logs to its file, also when tests run in parallel
+    let log = std::fs::read_to_string(experiment_dir.join(format!("{}.log", meta.experiment_id)))
+        .unwrap();
+    assert_eq!(log.matches("Measurement metadata").count(), 1);
+    assert!(log.contains(&meta.experiment_id));
+
+    measurement
+}
+
+#[test]
+fn benchmarks() {
+    let benchmarks: &[&[&str]] = &[
+        &["big_input", "16"],
+        &["big_input_push", "16"],
+        &["big_input_vecless", "16"],
+        &["decompress", "64"],
+        &["fib", "10"],
+        &["float", "4"],
+        &["float_fixed", "4"],
+        &["hello_world"],
+        &["matmul", "4"],
+        &["mlp", "4"],
+        &["mpt", "8", "2"],
+        &["sha2", "2"],
+        &["sort", "16"],
+        &["wasm", "10"],
+    ];
+
+    for benchmark in benchmarks {
+        let run = measure(&["--fake-proofs"], benchmark);
+        let Measurement(meta, metrics) = check(&run);
+
+        let bench::cli::Command::Measure {
+            benchmark: param, ..
+        } = &run.cli.command
+        else {
+            unreachable!()
+        };
+        assert_eq!(meta.benchmark_name, param.name());
+        assert_eq!(metrics.exec_output, Some(param.visit(HostOutput)));
+        assert_eq!(metrics.exec_output, metrics.segmented_proof_output);
+    }
+}
+
+#[test]
+fn compose() {
+    let run = measure(&["--fake-proofs"], &["compose", "2"]);
+    let Measurement(_, metrics) = check(&run);
+
+    assert_eq!(metrics.inner_proofs, Some(2));
+    assert_eq!(metrics.exec_output.as_deref(), Some("2"));
+}
+
+#[test]
+fn compose_exec_only() {
+    let run = measure(&["--phases=exec"], &["compose", "2"]);
+    let Measurement(_, metrics) = &run.measurements[0];
+
+    assert!(metrics
+        .error_string
+        .as_deref()
+        .is_some_and(|error| error.contains("need the prove phase")));
+    assert_eq!(metrics.inner_proofs, None);
+    assert_eq!(metrics.exec_output, None);
+}
+
+#[test]
+fn exec_only() {
+    let run = measure(&["--phases=exec"], &["fib", "10"]);
+    let Measurement(meta, metrics) = &run.measurements[0];
+
+    assert!(!meta.fake_proofs);
+    assert_eq!(metrics.error_string, None);
+    assert_eq!(metrics.skipped_phases.as_deref(), Some("prove;reduce;wrap"));
+    assert_eq!(metrics.segmented_prove_millis, None);
+    assert_eq!(metrics.exec_output.as_deref(), Some("89"));
+}
+
+#[test]
+fn wasm_n_fits_i32() {
+    let parse = |n| {
+        Cli::try_parse_from([
+            "bench",
+            "measure",
+            "--segment-size=20",
+            MACHINE_TAG,
+            "r0",
+            "wasm",
+            n,
+        ])
+    };
+    assert!(parse("2147483647").is_ok());
+    assert!(parse("2147483648").is_err());
+}
+
+#[test]
+fn mpt_accounts_at_least_1() {
+    let parse = |accounts| {
+        Cli::try_parse_from([
+            "bench",
+            "measure",
+            "--segment-size=20",
+            MACHINE_TAG,
+            "r0",
+            "mpt",
+            accounts,
+            "10",
+        ])
+    };
+    assert!(parse("1").is_ok());
+    assert!(parse("0").is_err());
+}
+
+#[test]
+fn mlp_width_at_least_1() {
+    let parse = |width| {
+        Cli::try_parse_from([
+            "bench",
+            "measure",
+            "--segment-size=20",
+            MACHINE_TAG,
+            "r0",
+            "mlp",
+            width,
+        ])
+    };
+    assert!(parse("1").is_ok());
+    assert!(parse("0").is_err());
+}
+
+/// `list` prints the subcommand names, which must match the recorded benchmark names.
+#[test]
+fn benchmark_names_round_trip() {
+    use clap::{FromArgMatches, Subcommand};
+
+    let command = common::BenchmarkParam::augment_subcommands(clap::Command::new("benchmark"));
+    for subcommand in command.get_subcommands() {
+        let args = ["benchmark", subcommand.get_name()]
+            .into_iter()
+            .chain(subcommand.get_positionals().map(|_| "1"));
+        let matches = command.clone().try_get_matches_from(args).unwrap();
+        let param = common::BenchmarkParam::from_arg_matches(&matches).unwrap();
+        assert_eq!(param.name(), subcommand.get_name());
+    }
+}
+
+/// The kebab-case names of the subcommands from before they were named like the benchmark.
+#[test]
+fn kebab_case_aliases() {
+    for (alias, name) in [
+        (&["big-input", "16"][..], "big_input"),
+        (&["big-input-push", "16"], "big_input_push"),
+        (&["big-input-vecless", "16"], "big_input_vecless"),
+        (&["float-fixed", "4"], "float_fixed"),
+        (&["hello-world"], "hello_world"),
+    ] {
+        let args = ["bench", "measure", "--segment-size=20", MACHINE_TAG, "r0"]
+            .into_iter()
+            .chain(alias.iter().copied());
+        let cli = Cli::try_parse_from(args).unwrap();
+        let bench::cli::Command::Measure { benchmark, .. } = &cli.command else {
+            unreachable!()
+        };
+        assert_eq!(benchmark.name(), name);
+    }
+}
+
+/// sp1 has no mock prover, so it executes and marks the proof phases unsupported.
+#[test]
+#[ignore = "needs the SP1 guest, which SP1_SKIP_BUILD leaves empty"]
+fn sp1_fake_proofs() {
+    let run = measure_vm("sp1", &["--fake-proofs"], &["fib", "10"]);
+    let Measurement(meta, metrics) = &run.measurements[0];
+
+    assert!(meta.fake_proofs);
+    assert_eq!(meta.vm, "sp1");
+    assert_eq!(metrics.error_string, None);
+    assert_eq!(metrics.unsupported.as_deref(), Some("prove;reduce;wrap"));
+    assert_eq!(metrics.exec_output.as_deref(), Some("89"));
+    assert_eq!(metrics.segmented_prove_millis, None);
+    assert_eq!(metrics.reduced_proof_kind, None);
+}
+
+#[test]
+#[ignore = "needs the SP1 guest, which SP1_SKIP_BUILD leaves empty"]
+fn sp1_exec_only() {
+    let run = measure_vm("sp1", &["--phases=exec"], &["fib", "10"]);
+    let Measurement(meta, metrics) = &run.measurements[0];
+
+    assert_eq!(meta.vm, "sp1");
+    assert!(meta
+        .sp1_vk_hash
+        .as_deref()
+        .is_some_and(|hash| hash.len() == 66 && hash.starts_with("0x")));
+    assert_eq!(metrics.error_string, None);
+    assert_eq!(metrics.skipped_phases.as_deref(), Some("prove;reduce;wrap"));
+    assert_eq!(metrics.exec_output.as_deref(), Some("89"));
+    assert!(metrics.exec_user_cycles.unwrap() > 0);
+
+    // From the cycle tracker regions the guest marks around each phase
+    for cycles in [
+        metrics.exec_decode_cycles,
+        metrics.exec_compute_cycles,
+        metrics.exec_commit_cycles,
+    ] {
+        assert!(cycles.unwrap() > 0);
+    }
+    assert!(metrics.exec_compute_cycles < metrics.exec_user_cycles);
+}
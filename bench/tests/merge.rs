@@ -0,0 +1,96 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bench::{cli::Cli, metric::Measurement, schema};
+use clap::Parser;
+use fixture::measurement;
+
+mod fixture;
+
+fn write(path: std::path::PathBuf, measurements: &[Measurement]) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    schema::write(std::fs::File::create(path).unwrap(), measurements).unwrap();
+}
+
+fn merge(datadir: &std::path::Path) -> Vec<String> {
+    let datadir_arg = format!("--datadir={}", datadir.display());
+    let cli = Cli::try_parse_from(["bench", &datadir_arg, "merge"]).unwrap();
+    cli.run().unwrap();
+
+    let csv_file = std::fs::File::open(cli.measurements_file_path()).unwrap();
+    let (measurements, rejected) = schema::read(csv_file).unwrap();
+    assert!(rejected.is_empty());
+
+    measurements
+        .into_iter()
+        .map(|Measurement(meta, _)| meta.experiment_id)
+        .collect()
+}
+
+#[test]
+fn merge_experiments() {
+    let datadir = tempfile::tempdir().unwrap();
+    let path = datadir.path();
+
+    // Rows from before experiments had their own files are kept
+    write(
+        path.join("measurements.csv"),
+        &[measurement()
+            .experiment_id("old")
+            .datetime("2024-05-01T00:00:00Z")
+            .build()],
+    );
+    write(
+        path.join("test").join("b.csv"),
+        &[measurement()
+            .experiment_id("b")
+            .datetime("2024-05-03T00:00:00Z")
+            .build()],
+    );
+    write(
+        path.join("other").join("a.csv"),
+        &[measurement()
+            .experiment_id("a")
+            .datetime("2024-05-02T00:00:00Z")
+            .build()],
+    );
+
+    assert_eq!(merge(path), ["old", "a", "b"]);
+
+    // Merging again only adds new experiments
+    write(
+        path.join("test").join("c.csv"),
+        &[measurement()
+            .experiment_id("c")
+            .datetime("2024-05-04T00:00:00Z")
+            .build()],
+    );
+    assert_eq!(merge(path), ["old", "a", "b", "c"]);
+}
+
+#[test]
+fn merge_datadir_with_glob_characters() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let path = tmpdir.path().join("data [*?]");
+
+    write(
+        path.join("test").join("a.csv"),
+        &[measurement()
+            .experiment_id("a")
+            .datetime("2024-05-02T00:00:00Z")
+            .build()],
+    );
+
+    assert_eq!(merge(&path), ["a"]);
+}
@@ -0,0 +1,143 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bench::{cli::Cli, metric::Measurement, schema};
+use clap::Parser;
+use fixture::measurement;
+
+mod fixture;
+
+fn fib(vm: &str, n: u32, prove_millis: u128) -> Measurement {
+    measurement()
+        .benchmark("fib", n, &format!(r#"{{"n":{}}}"#, n))
+        .vm(vm)
+        .prove_millis(prove_millis)
+        .build()
+}
+
+fn plot(datadir: &std::path::Path, args: &[&str]) -> anyhow::Result<()> {
+    let datadir_arg = format!("--datadir={}", datadir.display());
+    let cli = Cli::try_parse_from(["bench", &datadir_arg, "plot"].iter().chain(args))?;
+    cli.run()
+}
+
+#[test]
+fn plot_scaling() {
+    let datadir = tempfile::tempdir().unwrap();
+    let path = datadir.path();
+    schema::write(
+        std::fs::File::create(path.join("measurements.csv")).unwrap(),
+        &[
+            fib("r0", 100, 10),
+            fib("r0", 1000, 90),
+            fib("sp1", 100, 20),
+            fib("sp1", 1000, 150),
+            // Not drawn on a log scale
+            fib("sp1", 0, 0),
+        ],
+    )
+    .unwrap();
+
+    plot(path, &[]).unwrap();
+    let svg = std::fs::read_to_string(path.join("plots").join("fib.svg")).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("test r0 20"));
+    assert!(svg.contains("test sp1 20"));
+
+    // Filtered to one VM, the legend only has its line
+    let outdir = path.join("r0");
+    let outdir_arg = format!("--outdir={}", outdir.display());
+    plot(path, &["--where=vm=r0", "--group-by=vm", &outdir_arg]).unwrap();
+    let svg = std::fs::read_to_string(outdir.join("fib.svg")).unwrap();
+    let texts: Vec<&str> = svg.lines().map(str::trim).collect();
+    assert!(texts.contains(&"r0"));
+    assert!(!texts.contains(&"sp1"));
+}
+
+#[test]
+fn plot_params() {
+    let datadir = tempfile::tempdir().unwrap();
+    let path = datadir.path();
+    let mpt = |accounts: u32, updates: u32, segment_size: u32| {
+        let params = format!(r#"{{"accounts":{},"updates":{}}}"#, accounts, updates);
+        measurement()
+            .benchmark("mpt", updates, &params)
+            .segment_size(segment_size)
+            .prove_millis(10 * updates as u128)
+            .build()
+    };
+    schema::write(
+        std::fs::File::create(path.join("measurements.csv")).unwrap(),
+        &[
+            mpt(100, 10, 20),
+            mpt(100, 100, 20),
+            mpt(100, 100, 21),
+            // Same account count as updates, still in the chart of its account count
+            mpt(1000, 1000, 20),
+            mpt(1000, 10, 20),
+        ],
+    )
+    .unwrap();
+
+    plot(path, &[]).unwrap();
+    let mut charts: Vec<String> = std::fs::read_dir(path.join("plots"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    charts.sort();
+    assert_eq!(charts, ["mpt-accounts=100.svg", "mpt-accounts=1000.svg"]);
+
+    // Segment sizes are separate lines
+    let svg = std::fs::read_to_string(path.join("plots").join("mpt-accounts=100.svg")).unwrap();
+    assert!(svg.contains("test r0 20"));
+    assert!(svg.contains("test r0 21"));
+}
+
+#[test]
+fn plot_builds() {
+    let datadir = tempfile::tempdir().unwrap();
+    let path = datadir.path();
+    let mpt = |elf_sha256: &str, accounts: u32, updates: u32| {
+        let params = format!(r#"{{"accounts":{},"updates":{}}}"#, accounts, updates);
+        measurement()
+            .benchmark("mpt", updates, &params)
+            .elf_sha256(elf_sha256)
+            .prove_millis(updates as u128)
+            .build()
+    };
+    schema::write(
+        std::fs::File::create(path.join("measurements.csv")).unwrap(),
+        &[
+            mpt("aaaaaaaa00", 1, 100),
+            mpt("aaaaaaaa00", 1, 1000),
+            mpt("bbbbbbbb00", 1, 100),
+            mpt("bbbbbbbb00", 2, 100),
+        ],
+    )
+    .unwrap();
+
+    // Grouped by a benchmark param, with a line per build
+    plot(path, &["--group-by=accounts"]).unwrap();
+    let svg = std::fs::read_to_string(path.join("plots").join("mpt-accounts=1.svg")).unwrap();
+    let texts: Vec<&str> = svg.lines().map(str::trim).collect();
+    assert!(texts.contains(&"1 aaaaaaaa"));
+    assert!(texts.contains(&"1 bbbbbbbb"));
+}
+
+#[test]
+fn unknown_column() {
+    let datadir = tempfile::tempdir().unwrap();
+    assert!(plot(datadir.path(), &["--y=prove_seconds"]).is_err());
+    assert!(plot(datadir.path(), &["--where=speed=fast"]).is_err());
+}
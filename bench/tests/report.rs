@@ -0,0 +1,212 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bench::report::{self, Format};
+use clap::Parser;
+use fixture::measurement;
+
+mod fixture;
+
+fn report(format: Format) -> String {
+    let measurements = [
+        measurement()
+            .vm("r0")
+            .segment_size(20)
+            .prove_millis(100)
+            .build(),
+        measurement()
+            .vm("r0")
+            .segment_size(20)
+            .prove_millis(300)
+            .build(),
+        measurement()
+            .vm("r0")
+            .segment_size(21)
+            .prove_millis(150)
+            .build(),
+        measurement()
+            .vm("sp1")
+            .segment_size(22)
+            .prove_millis(400)
+            .build(),
+    ];
+    let mut out = vec![];
+    report::write(&mut out, &measurements, format).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn csv() {
+    let out = report(Format::Csv);
+    let mut rdr = csv::Reader::from_reader(out.as_bytes());
+    let header = rdr.headers().unwrap().clone();
+    let column = header
+        .iter()
+        .position(|column| column == "segmented_prove_millis")
+        .unwrap();
+    let rows: Vec<Vec<String>> = rdr
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            [2, 3, 4, 6, 7, column]
+                .iter()
+                .map(|i| record[*i].to_string())
+                .collect()
+        })
+        .collect();
+
+    // Runs are averaged per group, and the ratio compares the best group of each VM
+    assert_eq!(
+        rows,
+        [
+            ["100", r#"{"n":100}"#, "r0", "20", "2", "200"],
+            ["100", r#"{"n":100}"#, "r0", "21", "1", "150"],
+            ["100", r#"{"n":100}"#, "sp1", "22", "1", "400"],
+            ["100", r#"{"n":100}"#, "r0/sp1", "", "", "0.38"],
+        ]
+    );
+}
+
+#[test]
+fn markdown() {
+    let out = report(Format::Markdown);
+    assert!(out.starts_with(
+        "### test fib\n\n| benchmark_size | benchmark_params | vm | build | segment_size | runs |"
+    ));
+    assert!(out.contains(r#"| 100 | {"n":100} | r0/sp1 |  |  |  |"#));
+}
+
+#[test]
+fn table() {
+    let out = report(Format::Table);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "test fib");
+    // Columns are aligned
+    assert!(lines[2..7].iter().all(|line| line.len() == lines[2].len()));
+}
+
+#[test]
+fn params() {
+    // mpt at the same size, with different account counts
+    let mpt = |accounts: u32, vm: &str, prove_millis: u128| {
+        let params = format!(r#"{{"accounts":{},"updates":10}}"#, accounts);
+        measurement()
+            .benchmark("mpt", 10, &params)
+            .vm(vm)
+            .prove_millis(prove_millis)
+            .build()
+    };
+    let measurements = [
+        mpt(100, "r0", 100),
+        mpt(1000, "r0", 200),
+        mpt(100, "sp1", 400),
+    ];
+    let mut out = vec![];
+    report::write(&mut out, &measurements, Format::Csv).unwrap();
+
+    let mut rdr = csv::Reader::from_reader(&out[..]);
+    let rows: Vec<Vec<String>> = rdr
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            [3, 4, 7].iter().map(|i| record[*i].to_string()).collect()
+        })
+        .collect();
+
+    // Each account count has its own rows, and only the one with both VMs a ratio
+    assert_eq!(
+        rows,
+        [
+            [r#"{"accounts":100,"updates":10}"#, "r0", "1"],
+            [r#"{"accounts":100,"updates":10}"#, "sp1", "1"],
+            [r#"{"accounts":100,"updates":10}"#, "r0/sp1", ""],
+            [r#"{"accounts":1000,"updates":10}"#, "r0", "1"],
+        ]
+    );
+}
+
+#[test]
+fn builds() {
+    // Runs of two guest builds at the same size
+    let fib = |elf_sha256: &str, vm: &str, prove_millis: u128| {
+        measurement()
+            .elf_sha256(elf_sha256)
+            .vm(vm)
+            .prove_millis(prove_millis)
+            .build()
+    };
+    let measurements = [
+        fib("aaaaaaaa00", "r0", 100),
+        fib("bbbbbbbb00", "r0", 300),
+        fib("bbbbbbbb00", "r0", 500),
+        fib("cccccccc00", "sp1", 200),
+    ];
+    let mut out = vec![];
+    report::write(&mut out, &measurements, Format::Csv).unwrap();
+
+    let mut rdr = csv::Reader::from_reader(&out[..]);
+    let header = rdr.headers().unwrap().clone();
+    let column = header
+        .iter()
+        .position(|column| column == "segmented_prove_millis")
+        .unwrap();
+    let rows: Vec<Vec<String>> = rdr
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            [4, 5, 7, column]
+                .iter()
+                .map(|i| record[*i].to_string())
+                .collect()
+        })
+        .collect();
+
+    // Each build has its own row, and the ratio compares the best build of each VM
+    assert_eq!(
+        rows,
+        [
+            ["r0", "aaaaaaaa", "1", "100"],
+            ["r0", "bbbbbbbb", "2", "400"],
+            ["sp1", "cccccccc", "1", "200"],
+            ["r0/sp1", "", "", "0.50"],
+        ]
+    );
+}
+
+#[test]
+fn filter_params() {
+    #[derive(Parser)]
+    struct Args {
+        #[command(flatten)]
+        filter: report::Filter,
+    }
+    let filter =
+        |args: &[&str]| Args::try_parse_from(["report"].iter().chain(args)).map(|args| args.filter);
+
+    let mpt = |accounts: u32| {
+        let params = format!(r#"{{"accounts":{},"updates":10}}"#, accounts);
+        measurement().benchmark("mpt", 10, &params).build()
+    };
+    let selected = filter(&["--where=accounts=1000"])
+        .unwrap()
+        .apply(vec![mpt(100), mpt(1000)])
+        .unwrap();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(
+        selected[0].0.benchmark_params,
+        r#"{"accounts":1000,"updates":10}"#
+    );
+
+    assert!(filter(&["--where=speed=fast"]).is_err());
+}
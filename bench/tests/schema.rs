@@ -0,0 +1,79 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bench::{
+    metric::{Measurement, Meta, Metrics},
+    schema,
+};
+
+/// Header and row written before the schema was versioned.
+const UNVERSIONED: &str = "\
+machine_tag,benchmark_name,benchmark_size,vm,segment_size,experiment_id,datetime,r0_feature_flags,r0_version,sp1_save_disk_threshold,sp1_shard_batch_size,cli_args,error_string,input_bytes,exec_millis,exec_user_cycles,exec_output,prover_create_millis,segmented_prove_millis,segmented_poof_segments,segmented_proof_kind,segmented_proof_output,segmented_verify_millis,segmented_proof_w_metadata_bytes,segmented_proof_bytes,reduced_prove_millis,reduced_proof_kind,reduced_verify_millis,reduced_proof_w_metadata_bytes,reduced_proof_bytes
+mbp,fib,100,r0,20,e1,2024-05-01T00:00:00Z,metal,1.0.0-rc.5,,,bench measure,,8,10,4096,1,5,100,3,composite,1,2,300,200,50,succinct,1,250,220
+mbp,fib,not-a-number,r0,20,e2,2024-05-01T00:00:00Z,metal,1.0.0-rc.5,,,bench measure,,8,10,4096,1,5,100,3,composite,1,2,300,200,50,succinct,1,250,220
+";
+
+#[test]
+fn upgrade_unversioned() {
+    let (measurements, rejected) = schema::read(UNVERSIONED.as_bytes()).unwrap();
+
+    assert_eq!(measurements.len(), 1);
+    let Measurement(meta, metrics) = &measurements[0];
+    assert_eq!(meta.schema_version, schema::SCHEMA_VERSION);
+    assert_eq!(meta.experiment_id, "e1");
+    assert_eq!(meta.benchmark_params, "{}");
+    assert!(!meta.fake_proofs);
+    assert_eq!(metrics.exec_user_cycles, Some(4096));
+    assert_eq!(metrics.segmented_proof_segments, Some(3));
+    assert_eq!(metrics.reduced_proof_bytes, Some(220));
+    assert_eq!(metrics.wrapped_prove_millis, None);
+
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].line, 3);
+    assert_eq!(&rejected[0].record[5], "e2");
+}
+
+#[test]
+fn round_trip() {
+    let measurement = Measurement(
+        Meta {
+            machine_tag: "mbp".into(),
+            benchmark_name: "mpt".into(),
+            benchmark_params: r#"{"accounts":8,"updates":2}"#.into(),
+            fake_proofs: true,
+            ..Default::default()
+        },
+        Metrics {
+            exec_output: Some("[1, 2, 3]".into()),
+            segmented_segments: Some(r#"[{"cycles":1024,"po2":20}]"#.into()),
+            ..Default::default()
+        },
+    );
+
+    let mut data = vec![];
+    schema::write(&mut data, &[measurement.clone(), measurement]).unwrap();
+    let (measurements, rejected) = schema::read(data.as_slice()).unwrap();
+
+    assert!(rejected.is_empty());
+    assert_eq!(measurements.len(), 2);
+    let Measurement(meta, metrics) = &measurements[1];
+    assert_eq!(meta.schema_version, schema::SCHEMA_VERSION);
+    assert_eq!(meta.benchmark_params, r#"{"accounts":8,"updates":2}"#);
+    assert!(meta.fake_proofs);
+    assert_eq!(metrics.exec_output.as_deref(), Some("[1, 2, 3]"));
+    assert_eq!(
+        metrics.segmented_segments.as_deref(),
+        Some(r#"[{"cycles":1024,"po2":20}]"#)
+    );
+}
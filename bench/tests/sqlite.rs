@@ -0,0 +1,174 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bench::{cli::Cli, metric::Measurement, schema};
+use clap::Parser;
+use fixture::measurement;
+
+mod fixture;
+
+fn import(datadir: &std::path::Path, measurements: &[Measurement]) -> rusqlite::Connection {
+    let csv_file_path = datadir.join("measurements.csv");
+    schema::write(std::fs::File::create(&csv_file_path).unwrap(), measurements).unwrap();
+
+    let datadir_arg = format!("--datadir={}", datadir.display());
+    let cli = Cli::try_parse_from(["bench", &datadir_arg, "import"]).unwrap();
+    cli.run().unwrap();
+
+    rusqlite::Connection::open(cli.sqlite_file_path()).unwrap()
+}
+
+fn count(conn: &rusqlite::Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn import_csv() {
+    let datadir = tempfile::tempdir().unwrap();
+
+    let conn = import(
+        datadir.path(),
+        &[
+            measurement()
+                .experiment_id("a")
+                .exec_user_cycles(1000)
+                .segments(2)
+                .build(),
+            measurement()
+                .experiment_id("b")
+                .exec_user_cycles(1000)
+                .segments(3)
+                .build(),
+        ],
+    );
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM experiments"), 2);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM measurements"), 2);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM segments"), 5);
+    assert_eq!(
+        count(
+            &conn,
+            "SELECT SUM(exec_user_cycles) FROM experiments JOIN measurements USING (experiment_id)
+             WHERE benchmark_name = 'fib'"
+        ),
+        2000
+    );
+
+    // Importing again replaces the rows of the same experiments
+    let conn = import(
+        datadir.path(),
+        &[measurement()
+            .experiment_id("a")
+            .exec_user_cycles(1000)
+            .segments(1)
+            .build()],
+    );
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM experiments"), 2);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM segments"), 4);
+}
+
+#[test]
+fn import_u64_above_i64_max() {
+    let datadir = tempfile::tempdir().unwrap();
+
+    let large = measurement()
+        .experiment_id("a")
+        .exec_user_cycles(u64::MAX)
+        .build();
+    let conn = import(datadir.path(), &[large]);
+
+    let cycles: String = conn
+        .query_row("SELECT exec_user_cycles FROM measurements", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(cycles, u64::MAX.to_string());
+}
+
+/// Parallel first runs all create the tables and add the columns.
+#[test]
+fn open_in_parallel() {
+    let datadir = tempfile::tempdir().unwrap();
+    let path = datadir.path().join("measurements.sqlite");
+
+    std::thread::scope(|scope| {
+        let opens: Vec<_> = (0..8)
+            .map(|_| scope.spawn(|| bench::sqlite::open(&path)))
+            .collect();
+        for open in opens {
+            open.join().unwrap().unwrap();
+        }
+    });
+}
+
+#[test]
+fn measure_sqlite() {
+    let datadir = tempfile::tempdir().unwrap();
+    let datadir_arg = format!("--datadir={}", datadir.path().display());
+    let cli = Cli::try_parse_from([
+        "bench",
+        &datadir_arg,
+        "measure",
+        "--segment-size=20",
+        "--fake-proofs",
+        "--store=sqlite",
+        "test",
+        "r0",
+        "fib",
+        "100",
+    ])
+    .unwrap();
+    cli.run().unwrap();
+
+    let conn = rusqlite::Connection::open(cli.sqlite_file_path()).unwrap();
+    assert_eq!(
+        count(
+            &conn,
+            "SELECT COUNT(*) FROM experiments JOIN measurements USING (experiment_id)
+             WHERE vm = 'r0' AND fake_proofs AND exec_user_cycles > 0"
+        ),
+        1
+    );
+    assert!(count(&conn, "SELECT COUNT(*) FROM segments") > 0);
+}
+
+#[test]
+fn measure_sqlite_fallback() {
+    let datadir = tempfile::tempdir().unwrap();
+    let datadir_arg = format!("--datadir={}", datadir.path().display());
+    let cli = Cli::try_parse_from([
+        "bench",
+        &datadir_arg,
+        "measure",
+        "--segment-size=20",
+        "--phases=exec",
+        "--store=sqlite",
+        "test",
+        "r0",
+        "fib",
+        "10",
+    ])
+    .unwrap();
+    // A directory in place of the database fails to open
+    std::fs::create_dir(cli.sqlite_file_path()).unwrap();
+
+    let err = cli.run().unwrap_err();
+    assert!(err.to_string().contains("wrote"), "{:?}", err);
+
+    let merge = Cli::try_parse_from(["bench", &datadir_arg, "merge"]).unwrap();
+    merge.run().unwrap();
+    let csv_file = std::fs::File::open(cli.measurements_file_path()).unwrap();
+    let (measurements, _) = schema::read(csv_file).unwrap();
+    assert_eq!(measurements.len(), 1);
+    assert_eq!(measurements[0].1.exec_output.as_deref(), Some("89"));
+}
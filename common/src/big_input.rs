@@ -15,18 +15,28 @@
 use clap::Args;
 use serde::{Deserialize, Serialize};
 
-use crate::{BenchmarkInput, Runnable};
+use crate::{Benchmark, GuestEnv, Runnable};
 
 #[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Param {
     pub words: u32,
 }
 
-impl Param {
-    pub fn input(&self) -> Input {
-        Input {
+impl Benchmark for Param {
+    const NAME: &'static str = "big_input";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        let input = Input {
             words: (0..self.words).collect(),
-        }
+        };
+
+        (input, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.words
     }
 }
 
@@ -35,17 +45,10 @@ pub struct Input {
     pub words: Vec<u32>,
 }
 
-impl From<Input> for BenchmarkInput {
-    fn from(x: Input) -> Self {
-        Self::BigInput(x)
-    }
-}
-
 impl Runnable for Input {
     type Output = u32;
 
-    #[cfg(feature = "guest")]
-    fn run(self) -> anyhow::Result<Self::Output> {
-        Ok(self.words.into_iter().sum())
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        Ok(self.words.into_iter().fold(0, u32::wrapping_add))
     }
 }
@@ -15,21 +15,29 @@
 use clap::Args;
 use serde::{Deserialize, Serialize};
 
-use crate::{BenchmarkInput, Runnable};
+use crate::{Benchmark, GuestEnv, Runnable};
 
 #[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Param {
     pub words: u32,
 }
 
-impl Param {
-    pub fn input(&self) -> (Input, Vec<u32>) {
+impl Benchmark for Param {
+    const NAME: &'static str = "big_input_push";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
         let input = Input { words: self.words };
 
         let extra_input: Vec<u32> = (0..self.words).collect();
 
         (input, extra_input)
     }
+
+    fn size(&self) -> u32 {
+        self.words
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -37,31 +45,16 @@ pub struct Input {
     pub words: u32,
 }
 
-impl From<Input> for BenchmarkInput {
-    fn from(x: Input) -> Self {
-        Self::BigInputPush(x)
-    }
-}
-
 impl Runnable for Input {
     type Output = u32;
 
-    #[cfg(feature = "guest")]
-    fn run(self) -> anyhow::Result<Self::Output> {
+    fn run<E: GuestEnv>(self, env: &mut E) -> anyhow::Result<Self::Output> {
         let mut words: Vec<u32> = Vec::with_capacity(self.words as usize);
 
         for _ in 0..self.words {
-            #[cfg(feature = "r0")]
-            {
-                words.push(risc0_zkvm::guest::env::read());
-            }
-
-            #[cfg(feature = "sp1")]
-            {
-                words.push(sp1_zkvm::io::read());
-            }
+            words.push(env.read());
         }
 
-        Ok(words.into_iter().sum())
+        Ok(words.into_iter().fold(0, u32::wrapping_add))
     }
 }
@@ -0,0 +1,81 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::ensure;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{fib, Benchmark, BenchmarkInput, GuestEnv, Runnable};
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    pub proofs: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "compose";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        (
+            Input {
+                proofs: self.proofs,
+            },
+            vec![],
+        )
+    }
+
+    fn size(&self) -> u32 {
+        self.proofs
+    }
+}
+
+/// Verifies `proofs` inner proofs of this same guest.
+///
+/// The host proves [Runnable::inner] first, then writes the guest image ID / verifying key
+/// digest and the journal of every inner proof after the input, and adds the proofs as
+/// assumptions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub proofs: u32,
+}
+
+impl Runnable for Input {
+    type Output = u32;
+
+    fn run<E: GuestEnv>(self, env: &mut E) -> anyhow::Result<Self::Output> {
+        let image_id: [u32; 8] = env.read();
+        let journals: Vec<Vec<u8>> = env.read();
+        ensure!(
+            journals.len() == self.proofs as usize,
+            "expected {} inner journals, got {}",
+            self.proofs,
+            journals.len()
+        );
+
+        for journal in &journals {
+            env.verify(image_id, journal)?;
+        }
+
+        Ok(self.proofs)
+    }
+
+    /// Distinct inputs, so that every proof has its own claim.
+    fn inner(&self) -> Vec<BenchmarkInput> {
+        (0..self.proofs)
+            .map(|i| fib::Input { n: 100 + i }.into())
+            .collect()
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256, Benchmark, GuestEnv, Runnable};
+
+const WORDS: &[&str] = &[
+    "block",
+    "batch",
+    "commit",
+    "proof",
+    "receipt",
+    "segment",
+    "shard",
+    "state",
+    "root",
+    "account",
+    "balance",
+    "nonce",
+    "storage",
+    "transaction",
+    "witness",
+    "journal",
+];
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    pub n: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "decompress";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        let payload = self.payload();
+        let input = Input {
+            len: self.n,
+            compressed: miniz_oxide::deflate::compress_to_vec(&payload, 6),
+        };
+
+        (input, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
+    }
+}
+
+impl Param {
+    /// Deterministic, text-like payload of `n` bytes that compresses reasonably well.
+    pub fn payload(&self) -> Vec<u8> {
+        use rand::prelude::*;
+        use rand_chacha::ChaCha20Rng;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(self.n as u64);
+        let mut payload: Vec<u8> = Vec::with_capacity(self.n as usize + 16);
+
+        while payload.len() < self.n as usize {
+            payload.extend_from_slice(WORDS.choose(&mut rng).unwrap().as_bytes());
+            payload.push(b' ');
+        }
+        payload.truncate(self.n as usize);
+
+        payload
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub len: u32,
+    pub compressed: Vec<u8>,
+}
+
+impl Runnable for Input {
+    type Output = [u8; 32];
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        let payload = miniz_oxide::inflate::decompress_to_vec(&self.compressed)
+            .map_err(|err| anyhow::anyhow!("Decompress payload: {}", err))?;
+        anyhow::ensure!(
+            payload.len() == self.len as usize,
+            "Check decompressed length: {} bytes, expected {}",
+            payload.len(),
+            self.len
+        );
+
+        Ok(sha256(&payload))
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// I/O and introspection available to a benchmark, independent of the VM it runs on.
+///
+/// Implementations read the values written by the host in order, and commit values to the
+/// journal / public values in order.
+pub trait GuestEnv {
+    fn read<X: DeserializeOwned>(&mut self) -> X;
+
+    /// Reads one word per element, as written by the host from the extra input.
+    fn read_slice(&mut self, buf: &mut [u32]);
+
+    fn commit<X: Serialize>(&mut self, val: &X);
+
+    fn commit_slice(&mut self, buf: &[u8]);
+
+    /// Cycles executed so far, if the VM exposes an in-guest cycle counter.
+    fn cycle_count(&self) -> Option<u64>;
+
+    fn log(&self, msg: &str);
+
+    /// Verifies a proof of the guest with the given image ID / verifying key digest that
+    /// committed `journal`. The proof itself is supplied by the host as an assumption.
+    fn verify(&mut self, image_id: [u32; 8], journal: &[u8]) -> anyhow::Result<()>;
+
+    /// Marks the start of a named region for VMs that track cycles outside the guest.
+    fn region_start(&self, _region: &str) {}
+
+    /// Marks the end of a region opened with [GuestEnv::region_start].
+    fn region_end(&self, _region: &str) {}
+}
+
+#[cfg(feature = "r0")]
+pub struct R0Env;
+
+#[cfg(feature = "r0")]
+impl GuestEnv for R0Env {
+    fn read<X: DeserializeOwned>(&mut self) -> X {
+        risc0_zkvm::guest::env::read()
+    }
+
+    fn read_slice(&mut self, buf: &mut [u32]) {
+        risc0_zkvm::guest::env::read_slice(buf)
+    }
+
+    fn commit<X: Serialize>(&mut self, val: &X) {
+        risc0_zkvm::guest::env::commit(val)
+    }
+
+    fn commit_slice(&mut self, buf: &[u8]) {
+        risc0_zkvm::guest::env::commit_slice(buf)
+    }
+
+    fn cycle_count(&self) -> Option<u64> {
+        Some(risc0_zkvm::guest::env::cycle_count() as u64)
+    }
+
+    fn log(&self, msg: &str) {
+        risc0_zkvm::guest::env::log(msg)
+    }
+
+    fn verify(&mut self, image_id: [u32; 8], journal: &[u8]) -> anyhow::Result<()> {
+        risc0_zkvm::guest::env::verify(image_id, journal)
+            .map_err(|err| anyhow::anyhow!("verify assumption: {}", err))
+    }
+}
+
+#[cfg(feature = "sp1")]
+pub struct Sp1Env;
+
+#[cfg(feature = "sp1")]
+impl GuestEnv for Sp1Env {
+    fn read<X: DeserializeOwned>(&mut self) -> X {
+        sp1_zkvm::io::read()
+    }
+
+    fn read_slice(&mut self, buf: &mut [u32]) {
+        // The host writes every extra word as its own input buffer.
+        for word in buf {
+            *word = sp1_zkvm::io::read();
+        }
+    }
+
+    fn commit<X: Serialize>(&mut self, val: &X) {
+        sp1_zkvm::io::commit(val)
+    }
+
+    fn commit_slice(&mut self, buf: &[u8]) {
+        sp1_zkvm::io::commit_slice(buf)
+    }
+
+    fn cycle_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn log(&self, msg: &str) {
+        println!("{}", msg)
+    }
+
+    fn verify(&mut self, image_id: [u32; 8], journal: &[u8]) -> anyhow::Result<()> {
+        // SP1 verifies the digest of the public values, and panics if no proof matches.
+        sp1_zkvm::lib::verify::verify_sp1_proof(&image_id, &crate::sha256(journal));
+        Ok(())
+    }
+
+    fn region_start(&self, region: &str) {
+        println!("cycle-tracker-start: {}", region)
+    }
+
+    fn region_end(&self, region: &str) {
+        println!("cycle-tracker-end: {}", region)
+    }
+}
+
+/// Runs benchmarks natively, e.g. for tests.
+#[cfg(feature = "host")]
+#[derive(Clone, Debug, Default)]
+pub struct HostEnv {
+    input: std::collections::VecDeque<serde_json::Value>,
+    journal: std::collections::VecDeque<serde_json::Value>,
+}
+
+#[cfg(feature = "host")]
+impl HostEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write<X: Serialize>(&mut self, val: &X) -> &mut Self {
+        self.input
+            .push_back(serde_json::to_value(val).expect("serialize host input"));
+        self
+    }
+
+    /// Reads the next committed value.
+    pub fn read_journal<X: DeserializeOwned>(&mut self) -> anyhow::Result<X> {
+        let val = self
+            .journal
+            .pop_front()
+            .ok_or_else(|| anyhow::anyhow!("journal is empty"))?;
+        Ok(serde_json::from_value(val)?)
+    }
+}
+
+#[cfg(feature = "host")]
+impl GuestEnv for HostEnv {
+    fn read<X: DeserializeOwned>(&mut self) -> X {
+        let val = self.input.pop_front().expect("host input is exhausted");
+        serde_json::from_value(val).expect("deserialize host input")
+    }
+
+    fn read_slice(&mut self, buf: &mut [u32]) {
+        for word in buf {
+            *word = self.read();
+        }
+    }
+
+    fn commit<X: Serialize>(&mut self, val: &X) {
+        self.journal
+            .push_back(serde_json::to_value(val).expect("serialize journal"));
+    }
+
+    fn commit_slice(&mut self, buf: &[u8]) {
+        self.commit(&buf)
+    }
+
+    fn cycle_count(&self) -> Option<u64> {
+        None
+    }
+
+    fn log(&self, msg: &str) {
+        eprintln!("{}", msg)
+    }
+
+    /// There are no proofs on the host, so every verification succeeds.
+    fn verify(&mut self, _image_id: [u32; 8], _journal: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
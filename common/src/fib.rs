@@ -15,16 +15,24 @@
 use clap::Args;
 use serde::{Deserialize, Serialize};
 
-use crate::{BenchmarkInput, Runnable};
+use crate::{Benchmark, GuestEnv, Runnable};
 
 #[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Param {
     pub n: u32,
 }
 
-impl Param {
-    pub fn input(&self) -> Input {
-        Input { n: self.n }
+impl Benchmark for Param {
+    const NAME: &'static str = "fib";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        (Input { n: self.n }, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
     }
 }
 
@@ -33,22 +41,15 @@ pub struct Input {
     pub n: u32,
 }
 
-impl From<Input> for BenchmarkInput {
-    fn from(x: Input) -> Self {
-        Self::Fib(x)
-    }
-}
-
 impl Runnable for Input {
     type Output = u32;
 
-    #[cfg(feature = "guest")]
-    fn run(self) -> anyhow::Result<Self::Output> {
-        let mut a = 0;
-        let mut b = 1;
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        let mut a: u32 = 0;
+        let mut b: u32 = 1;
 
         for _ in 0..self.n {
-            let c = a + b;
+            let c = a.wrapping_add(b);
             a = b;
             b = c;
         }
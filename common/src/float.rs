@@ -0,0 +1,86 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{Benchmark, GuestEnv, Runnable};
+
+/// Side length, in points, of the Mandelbrot tile.
+pub const TILE: u32 = 32;
+
+/// Region of the complex plane covered by the tile.
+pub const X_MIN: f64 = -2.0;
+pub const X_MAX: f64 = 0.5;
+pub const Y_MIN: f64 = -1.25;
+pub const Y_MAX: f64 = 1.25;
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    pub n: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "float";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        (Input { n: self.n }, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub n: u32,
+}
+
+impl Runnable for Input {
+    type Output = u32;
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        let x_step = (X_MAX - X_MIN) / TILE as f64;
+        let y_step = (Y_MAX - Y_MIN) / TILE as f64;
+
+        let mut out = 0;
+        for py in 0..TILE {
+            let cy = Y_MIN + y_step * py as f64;
+            for px in 0..TILE {
+                let cx = X_MIN + x_step * px as f64;
+
+                let mut x = 0.0f64;
+                let mut y = 0.0f64;
+                let mut i = 0;
+                while i < self.n {
+                    let (xx, yy) = (x * x, y * y);
+                    if xx + yy > 4.0 {
+                        break;
+                    }
+
+                    y = 2.0 * x * y + cy;
+                    x = xx - yy + cx;
+                    i += 1;
+                }
+
+                out += i;
+            }
+        }
+
+        Ok(out)
+    }
+}
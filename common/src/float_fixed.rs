@@ -0,0 +1,99 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    float::{TILE, X_MAX, X_MIN, Y_MAX, Y_MIN},
+    Benchmark, GuestEnv, Runnable,
+};
+
+/// Number of fractional bits in the Q4.27 fixed-point representation.
+pub const FRAC_BITS: u32 = 27;
+
+const ONE: f64 = (1u32 << FRAC_BITS) as f64;
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    pub n: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "float_fixed";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        // The tile bounds are converted on the host so that the guest never touches floats.
+        let input = Input {
+            n: self.n,
+            x_min: (X_MIN * ONE) as i32,
+            y_min: (Y_MIN * ONE) as i32,
+            x_step: ((X_MAX - X_MIN) / TILE as f64 * ONE) as i32,
+            y_step: ((Y_MAX - Y_MIN) / TILE as f64 * ONE) as i32,
+        };
+
+        (input, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub n: u32,
+    pub x_min: i32,
+    pub y_min: i32,
+    pub x_step: i32,
+    pub y_step: i32,
+}
+
+impl Runnable for Input {
+    type Output = u32;
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        // Products are kept in i64 since |z|^2 may exceed the Q4.27 range before escaping.
+        let mul = |a: i32, b: i32| (a as i64 * b as i64) >> FRAC_BITS;
+        let four = 4i64 << FRAC_BITS;
+
+        let mut out = 0;
+        for py in 0..TILE as i32 {
+            let cy = self.y_min + self.y_step * py;
+            for px in 0..TILE as i32 {
+                let cx = self.x_min + self.x_step * px;
+
+                let mut x = 0i32;
+                let mut y = 0i32;
+                let mut i = 0;
+                while i < self.n {
+                    let (xx, yy) = (mul(x, x), mul(y, y));
+                    if xx + yy > four {
+                        break;
+                    }
+
+                    y = (2 * mul(x, y) + cy as i64) as i32;
+                    x = (xx - yy + cx as i64) as i32;
+                    i += 1;
+                }
+
+                out += i;
+            }
+        }
+
+        Ok(out)
+    }
+}
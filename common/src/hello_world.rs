@@ -15,31 +15,32 @@
 use clap::Args;
 use serde::{Deserialize, Serialize};
 
-use crate::{BenchmarkInput, Runnable};
+use crate::{Benchmark, GuestEnv, Runnable};
 
 #[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Param {}
 
-impl Param {
-    pub fn input(&self) -> Input {
-        Input {}
+impl Benchmark for Param {
+    const NAME: &'static str = "hello_world";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        (Input {}, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        0
     }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Input {}
 
-impl From<Input> for BenchmarkInput {
-    fn from(x: Input) -> Self {
-        Self::HelloWorld(x)
-    }
-}
-
 impl Runnable for Input {
     type Output = ();
 
-    #[cfg(feature = "guest")]
-    fn run(self) -> anyhow::Result<Self::Output> {
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
         Ok(())
     }
 }
@@ -16,102 +16,203 @@ use anyhow::Result;
 use clap::Subcommand;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-pub mod big_input;
-pub mod big_input_push;
-pub mod big_input_vecless;
-pub mod fib;
-pub mod hello_world;
-pub mod sha2;
-pub mod sort;
-
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Subcommand)]
-pub enum BenchmarkParam {
-    BigInput(big_input::Param),
-    BigInputPush(big_input_push::Param),
-    BigInputVecless(big_input_vecless::Param),
-    Fib(fib::Param),
-    HelloWorld(hello_world::Param),
-    Sha2(sha2::Param),
-    Sort(sort::Param),
-}
+pub mod env;
+
+pub use env::GuestEnv;
+
+/// Version of this crate, which is compiled into every guest.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-impl BenchmarkParam {
-    pub fn name(&self) -> String {
-        match self {
-            BenchmarkParam::BigInput(_) => "big_input".into(),
-            BenchmarkParam::BigInputPush(_) => "big_input_push".into(),
-            BenchmarkParam::BigInputVecless(_) => "big_input_vecless".into(),
-            BenchmarkParam::Fib(_) => "fib".into(),
-            BenchmarkParam::HelloWorld(_) => "hello_world".into(),
-            BenchmarkParam::Sha2(_) => "sha2".into(),
-            BenchmarkParam::Sort(_) => "sort".into(),
+/// Declares every benchmark module and generates the `BenchmarkParam` and `BenchmarkInput`
+/// enums that dispatch to them.
+///
+/// Adding a benchmark only requires a new module implementing [Benchmark] for its `Param` and
+/// [Runnable] for its `Input`, plus one line here.
+macro_rules! benchmarks {
+    ($($variant:ident => $module:ident $(| $alias:literal)?),* $(,)?) => {
+        $(pub mod $module;)*
+
+        /// Subcommands are named like the module, which is also the recorded benchmark name.
+        /// The alias keeps the kebab-case name they had before working.
+        #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Subcommand)]
+        pub enum BenchmarkParam {
+            $(
+                #[command(name = stringify!($module) $(, alias = $alias)?)]
+                $variant($module::Param),
+            )*
         }
-    }
 
-    pub fn size(&self) -> u32 {
-        match self {
-            BenchmarkParam::BigInput(param) => param.words,
-            BenchmarkParam::BigInputPush(param) => param.words,
-            BenchmarkParam::BigInputVecless(param) => param.words,
-            BenchmarkParam::Fib(param) => param.n,
-            BenchmarkParam::HelloWorld(_) => 0,
-            BenchmarkParam::Sha2(param) => param.n,
-            BenchmarkParam::Sort(param) => param.n,
+        impl BenchmarkParam {
+            pub fn name(&self) -> String {
+                match self {
+                    $(BenchmarkParam::$variant(_) => <$module::Param as Benchmark>::NAME.into(),)*
+                }
+            }
+
+            pub fn size(&self) -> u32 {
+                match self {
+                    $(BenchmarkParam::$variant(param) => param.size(),)*
+                }
+            }
+
+            pub fn params(&self) -> Params {
+                match self {
+                    $(BenchmarkParam::$variant(param) => param.params(),)*
+                }
+            }
+
+            pub fn visit<V: BenchmarkVisitor>(&self, visitor: V) -> V::Output {
+                match self {
+                    $(BenchmarkParam::$variant(param) => visitor.visit(param),)*
+                }
+            }
         }
-    }
+
+        #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+        pub enum BenchmarkInput {
+            $($variant($module::Input),)*
+        }
+
+        $(
+            impl From<$module::Input> for BenchmarkInput {
+                fn from(x: $module::Input) -> Self {
+                    Self::$variant(x)
+                }
+            }
+        )*
+
+        impl BenchmarkInput {
+            pub fn run<E: GuestEnv>(self, env: &mut E, cycles: &mut Cycles) -> anyhow::Result<()> {
+                match self {
+                    $(BenchmarkInput::$variant(x) => run_and_commit(x, env, cycles),)*
+                }
+            }
+        }
+    };
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub enum BenchmarkInput {
-    BigInput(big_input::Input),
-    BigInputPush(big_input_push::Input),
-    BigInputVecless(big_input_vecless::Input),
-    Fib(fib::Input),
-    HelloWorld(hello_world::Input),
-    Sha2(sha2::Input),
-    Sort(sort::Input),
+benchmarks! {
+    BigInput => big_input | "big-input",
+    BigInputPush => big_input_push | "big-input-push",
+    BigInputVecless => big_input_vecless | "big-input-vecless",
+    Compose => compose,
+    Decompress => decompress,
+    Fib => fib,
+    Float => float,
+    FloatFixed => float_fixed | "float-fixed",
+    HelloWorld => hello_world | "hello-world",
+    Matmul => matmul,
+    Mlp => mlp,
+    Mpt => mpt,
+    Sha2 => sha2,
+    Sort => sort,
+    Wasm => wasm,
 }
 
-impl BenchmarkInput {
-    #[cfg(feature = "guest")]
-    pub fn run(self) -> anyhow::Result<()> {
-        match self {
-            BenchmarkInput::BigInput(x) => commit(&x.run().unwrap()),
-            BenchmarkInput::BigInputPush(x) => commit(&x.run().unwrap()),
-            BenchmarkInput::BigInputVecless(x) => commit(&x.run().unwrap()),
-            BenchmarkInput::Fib(x) => commit(&x.run().unwrap()),
-            BenchmarkInput::HelloWorld(x) => commit(&x.run().unwrap()),
-            BenchmarkInput::Sha2(x) => commit(&x.run().unwrap()),
-            BenchmarkInput::Sort(x) => commit(&x.run().unwrap()),
-        }
+/// Named parameters of a benchmark, e.g. `{"accounts": 1024, "updates": 16}`.
+pub type Params = serde_json::Map<String, serde_json::Value>;
+
+pub trait Benchmark
+where
+    Self: Serialize,
+{
+    /// Name recorded in measurements.
+    const NAME: &'static str;
 
-        Ok(())
+    type Input: Clone + Into<BenchmarkInput> + Runnable + Serialize;
+
+    /// Builds the guest input, plus any words written to the guest after it.
+    fn input(&self) -> (Self::Input, Vec<u32>);
+
+    fn size(&self) -> u32;
+
+    fn params(&self) -> Params {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(params)) => params,
+            _ => unreachable!("benchmark params serialize to a JSON object"),
+        }
     }
 }
 
+/// Operation that is generic over the concrete benchmark, see [BenchmarkParam::visit].
+pub trait BenchmarkVisitor {
+    type Output;
+
+    fn visit<B: Benchmark>(self, benchmark: &B) -> Self::Output;
+}
+
 pub trait Runnable
 where
     Self: Sized,
 {
     type Output: std::fmt::Debug + DeserializeOwned + Eq + PartialEq + Serialize;
 
-    fn run(self) -> Result<Self::Output> {
-        unimplemented!()
+    fn run<E: GuestEnv>(self, env: &mut E) -> Result<Self::Output>;
+
+    /// Inputs of the proofs this benchmark verifies. The host proves them first, writes the
+    /// guest image ID / verifying key digest and their journals after the input, and adds the
+    /// proofs as assumptions.
+    fn inner(&self) -> Vec<BenchmarkInput> {
+        vec![]
     }
 }
 
-#[cfg(feature = "guest")]
-pub fn commit<X: Serialize>(val: &X) {
+/// Guest cycles spent in each phase of a benchmark, committed after the benchmark output.
+///
+/// Phases are `None` when the VM has no in-guest cycle counter. SP1 reports them through its
+/// cycle tracker in the executor logs instead.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Cycles {
+    pub decode: Option<u64>,
+    pub compute: Option<u64>,
+    pub commit: Option<u64>,
+}
+
+/// Guest entrypoint shared by every VM.
+pub fn run<E: GuestEnv>(env: &mut E) -> Result<()> {
+    let mut cycles = Cycles::default();
+
+    let input: BenchmarkInput = track(env, "decode", &mut cycles.decode, |env| env.read());
+    input.run(env, &mut cycles)?;
+
+    env.commit(&cycles);
+
+    Ok(())
+}
+
+fn run_and_commit<X: Runnable, E: GuestEnv>(x: X, env: &mut E, cycles: &mut Cycles) -> Result<()> {
+    let output = track(env, "compute", &mut cycles.compute, |env| x.run(env))?;
+    track(env, "commit", &mut cycles.commit, |env| env.commit(&output));
+
+    Ok(())
+}
+
+fn track<E, X, F>(env: &mut E, region: &str, cycles: &mut Option<u64>, f: F) -> X
+where
+    E: GuestEnv,
+    F: FnOnce(&mut E) -> X,
+{
+    env.region_start(region);
+
+    let start = env.cycle_count();
+    let out = f(env);
+    *cycles = env.cycle_count().zip(start).map(|(end, start)| end - start);
+
+    env.region_end(region);
+
+    out
+}
+
+/// SHA-256, using the accelerated implementation of the VM when built for a guest.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
     #[cfg(not(any(feature = "r0", feature = "sp1")))]
-    compile_error!("feature \"r0\" or feature \"sp1\" must be enabled");
+    use ::sha2::{Digest, Sha256};
 
     #[cfg(feature = "r0")]
-    {
-        risc0_zkvm::guest::env::commit(val);
-    }
+    use sha2_r0::{Digest, Sha256};
 
     #[cfg(feature = "sp1")]
-    {
-        sp1_zkvm::io::commit(val);
-    }
+    use sha2_sp1::{Digest, Sha256};
+
+    Sha256::digest(data).into()
 }
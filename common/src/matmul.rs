@@ -0,0 +1,77 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{Benchmark, GuestEnv, Runnable};
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    pub n: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "matmul";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        use rand::prelude::*;
+        use rand_chacha::ChaCha20Rng;
+
+        let len = (self.n as usize) * (self.n as usize);
+        let mut rng = ChaCha20Rng::seed_from_u64(self.n as u64);
+
+        let a = (0..len).map(|_| rng.gen()).collect();
+        let b = (0..len).map(|_| rng.gen()).collect();
+
+        (Input { n: self.n, a, b }, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
+    }
+}
+
+/// Two `n` x `n` matrices, stored in row-major order.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub n: u32,
+    pub a: Vec<u32>,
+    pub b: Vec<u32>,
+}
+
+impl Runnable for Input {
+    type Output = u32;
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        let n = self.n as usize;
+        anyhow::ensure!(self.a.len() == n * n, "matrix a is not {n}x{n}");
+        anyhow::ensure!(self.b.len() == n * n, "matrix b is not {n}x{n}");
+
+        let mut c = vec![0u32; n * n];
+        for i in 0..n {
+            for k in 0..n {
+                let a_ik = self.a[i * n + k];
+                for j in 0..n {
+                    c[i * n + j] = c[i * n + j].wrapping_add(a_ik.wrapping_mul(self.b[k * n + j]));
+                }
+            }
+        }
+
+        // Checksum of the product
+        Ok(c.into_iter().fold(0, u32::wrapping_add))
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{Benchmark, GuestEnv, Runnable};
+
+/// Number of fully connected layers in the network.
+pub const LAYERS: usize = 4;
+
+/// Number of fractional bits in the Q16.16 fixed-point representation.
+pub const FRAC_BITS: u32 = 16;
+
+const ONE: i32 = 1 << FRAC_BITS;
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    /// Width of every layer in the network.
+    #[arg(value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "mlp";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        use rand::prelude::*;
+        use rand_chacha::ChaCha20Rng;
+
+        let width = self.width as usize;
+        let mut rng = ChaCha20Rng::seed_from_u64(self.width as u64);
+
+        // Keep weights around 1/width so activations stay in range across layers.
+        let scale = (ONE / self.width as i32).max(1);
+
+        let input = (0..width).map(|_| rng.gen_range(-ONE..=ONE)).collect();
+        let layers = (0..LAYERS)
+            .map(|_| Layer {
+                weights: (0..width * width)
+                    .map(|_| rng.gen_range(-scale..=scale))
+                    .collect(),
+                biases: (0..width).map(|_| rng.gen_range(-scale..=scale)).collect(),
+            })
+            .collect();
+
+        (Input { input, layers }, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.width
+    }
+}
+
+/// A fully connected layer with `width` x `width` weights in row-major order.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Layer {
+    pub weights: Vec<i32>,
+    pub biases: Vec<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub input: Vec<i32>,
+    pub layers: Vec<Layer>,
+}
+
+impl Runnable for Input {
+    type Output = u32;
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        let width = self.input.len();
+        anyhow::ensure!(width > 0, "network has no width");
+        let mut activations = self.input;
+
+        for layer in self.layers {
+            anyhow::ensure!(layer.weights.len() == width * width, "bad weights shape");
+            anyhow::ensure!(layer.biases.len() == width, "bad biases shape");
+
+            activations = layer
+                .weights
+                .chunks_exact(width)
+                .zip(layer.biases)
+                .map(|(row, bias)| {
+                    let acc = row
+                        .iter()
+                        .zip(&activations)
+                        .map(|(w, x)| *w as i64 * *x as i64)
+                        .sum::<i64>();
+                    let out = (acc >> FRAC_BITS) + bias as i64;
+
+                    // ReLU, saturating at the top of the fixed-point range
+                    out.clamp(0, i32::MAX as i64) as i32
+                })
+                .collect();
+        }
+
+        // Checksum of the final activations
+        Ok(activations
+            .into_iter()
+            .fold(0, |acc, x| acc.wrapping_add(x as u32)))
+    }
+}
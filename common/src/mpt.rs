@@ -0,0 +1,262 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{sha256, Benchmark, GuestEnv, Runnable};
+
+pub type Hash = [u8; 32];
+
+/// Hash of an empty subtree.
+pub const EMPTY: Hash = [0; 32];
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    /// Number of accounts in the trie.
+    #[arg(value_parser = clap::value_parser!(u32).range(1..))]
+    pub accounts: u32,
+
+    /// Number of account updates applied by the guest.
+    pub updates: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "mpt";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        use rand::prelude::*;
+        use rand_chacha::ChaCha20Rng;
+
+        let mut rng =
+            ChaCha20Rng::seed_from_u64(((self.accounts as u64) << 32) | self.updates as u64);
+
+        let balances: Vec<u64> = (0..self.accounts).map(|_| rng.gen()).collect();
+        let mut trie = Trie::new(&balances);
+        let root = trie.root();
+
+        let mut updates = Vec::with_capacity(self.updates as usize);
+        for _ in 0..self.updates {
+            let index = rng.gen_range(0..self.accounts);
+            let new_balance = rng.gen();
+
+            updates.push(Update {
+                index,
+                old_balance: trie.balance(index),
+                new_balance,
+                siblings: trie.siblings(index),
+            });
+
+            trie.update(index, new_balance);
+        }
+
+        (Input { root, updates }, vec![])
+    }
+
+    /// The updates are the work done by the guest. The account count sets the depth of the
+    /// paths, and is recorded with the other parameters in `benchmark_params`.
+    fn size(&self) -> u32 {
+        self.updates
+    }
+}
+
+/// Witness for a single account update, relative to the state left by the previous update.
+///
+/// `siblings` are the hashes next to the path of the account, from the root down to where the
+/// account is the only one left in its subtree.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Update {
+    pub index: u32,
+    pub old_balance: u64,
+    pub new_balance: u64,
+    pub siblings: Vec<Hash>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub root: Hash,
+    pub updates: Vec<Update>,
+}
+
+impl Runnable for Input {
+    type Output = Hash;
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        let mut root = self.root;
+
+        for update in self.updates {
+            let key = key(update.index);
+            anyhow::ensure!(
+                update.siblings.len() <= 256,
+                "path of account {} is longer than its key",
+                update.index
+            );
+
+            let old_leaf = leaf(&key, update.old_balance);
+            anyhow::ensure!(
+                fold(&key, old_leaf, &update.siblings) == root,
+                "invalid witness for account {}",
+                update.index
+            );
+
+            let new_leaf = leaf(&key, update.new_balance);
+            root = fold(&key, new_leaf, &update.siblings);
+        }
+
+        Ok(root)
+    }
+}
+
+/// Sparse Merkle trie of the account balances, keyed by the hash of the account index.
+///
+/// The path of a key follows its bits from the most significant one. A subtree without
+/// accounts hashes to [EMPTY], and a subtree with a single account is that account's leaf,
+/// so paths are only as deep as needed to tell the keys apart, like in a Patricia trie.
+struct Trie {
+    balances: Vec<u64>,
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+struct Node {
+    hash: Hash,
+    /// Left and right child, `None` for leaves and empty subtrees.
+    children: Option<(usize, usize)>,
+}
+
+impl Trie {
+    fn new(balances: &[u64]) -> Self {
+        let mut leaves: Vec<(Hash, u32)> = (0..balances.len() as u32)
+            .map(|index| (key(index), index))
+            .collect();
+        leaves.sort();
+
+        let mut trie = Trie {
+            balances: balances.to_vec(),
+            nodes: vec![],
+            root: 0,
+        };
+        trie.root = trie.build(&leaves, 0);
+        trie
+    }
+
+    /// Adds the subtree of the sorted leaves, whose keys share their first `depth` bits.
+    fn build(&mut self, leaves: &[(Hash, u32)], depth: usize) -> usize {
+        let node = match leaves {
+            [] => Node {
+                hash: EMPTY,
+                children: None,
+            },
+            [(key, index)] => Node {
+                hash: leaf(key, self.balances[*index as usize]),
+                children: None,
+            },
+            _ => {
+                let mid = leaves.partition_point(|(key, _)| !bit(key, depth));
+                let left = self.build(&leaves[..mid], depth + 1);
+                let right = self.build(&leaves[mid..], depth + 1);
+                Node {
+                    hash: node(&self.nodes[left].hash, &self.nodes[right].hash),
+                    children: Some((left, right)),
+                }
+            }
+        };
+
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn root(&self) -> Hash {
+        self.nodes[self.root].hash
+    }
+
+    fn balance(&self, index: u32) -> u64 {
+        self.balances[index as usize]
+    }
+
+    /// Nodes from the root down to the leaf of the key.
+    fn path(&self, key: &Hash) -> Vec<usize> {
+        let mut path = vec![self.root];
+        while let Some((left, right)) = self.nodes[*path.last().unwrap()].children {
+            path.push(if bit(key, path.len() - 1) {
+                right
+            } else {
+                left
+            });
+        }
+        path
+    }
+
+    fn siblings(&self, index: u32) -> Vec<Hash> {
+        let key = key(index);
+        let path = self.path(&key);
+        path.windows(2)
+            .map(|pair| {
+                let (left, right) = self.nodes[pair[0]].children.unwrap();
+                let sibling = if pair[1] == left { right } else { left };
+                self.nodes[sibling].hash
+            })
+            .collect()
+    }
+
+    fn update(&mut self, index: u32, balance: u64) {
+        self.balances[index as usize] = balance;
+
+        let key = key(index);
+        let path = self.path(&key);
+        self.nodes[*path.last().unwrap()].hash = leaf(&key, balance);
+        for &id in path.iter().rev().skip(1) {
+            let (left, right) = self.nodes[id].children.unwrap();
+            self.nodes[id].hash = node(&self.nodes[left].hash, &self.nodes[right].hash);
+        }
+    }
+}
+
+/// Bit of the key at the given depth, from the most significant bit.
+fn bit(key: &Hash, depth: usize) -> bool {
+    key[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+fn fold(key: &Hash, leaf: Hash, siblings: &[Hash]) -> Hash {
+    let mut hash = leaf;
+    for (depth, sibling) in siblings.iter().enumerate().rev() {
+        hash = if bit(key, depth) {
+            node(sibling, &hash)
+        } else {
+            node(&hash, sibling)
+        };
+    }
+    hash
+}
+
+fn key(index: u32) -> Hash {
+    sha256(&index.to_le_bytes())
+}
+
+/// Leaves and inner nodes start with different bytes, so a leaf can not pass for a node.
+fn leaf(key: &Hash, balance: u64) -> Hash {
+    let mut data = [0u8; 41];
+    data[1..33].copy_from_slice(key);
+    data[33..].copy_from_slice(&balance.to_le_bytes());
+    sha256(&data)
+}
+
+fn node(left: &Hash, right: &Hash) -> Hash {
+    let mut data = [1u8; 65];
+    data[1..33].copy_from_slice(left);
+    data[33..].copy_from_slice(right);
+    sha256(&data)
+}
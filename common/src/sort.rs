@@ -15,16 +15,24 @@
 use clap::Args;
 use serde::{Deserialize, Serialize};
 
-use crate::{BenchmarkInput, Runnable};
+use crate::{Benchmark, GuestEnv, Runnable};
 
 #[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Param {
     pub n: u32,
 }
 
-impl Param {
-    pub fn input(&self) -> Input {
-        Input { n: self.n }
+impl Benchmark for Param {
+    const NAME: &'static str = "sort";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        (Input { n: self.n }, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
     }
 }
 
@@ -33,17 +41,10 @@ pub struct Input {
     pub n: u32,
 }
 
-impl From<Input> for BenchmarkInput {
-    fn from(x: Input) -> Self {
-        Self::Sort(x)
-    }
-}
-
 impl Runnable for Input {
     type Output = u32;
 
-    #[cfg(feature = "guest")]
-    fn run(self) -> anyhow::Result<Self::Output> {
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
         use rand::prelude::*;
         use rand_chacha::ChaCha20Rng;
 
@@ -56,13 +57,13 @@ impl Runnable for Input {
 
         words.sort();
 
-        let mut out = 0;
+        let mut out: u32 = 0;
         let mut sgn = true;
         for val in words {
             if sgn {
-                out = out + val;
+                out = out.wrapping_add(val);
             } else {
-                out = out - val;
+                out = out.wrapping_sub(val);
             }
 
             sgn = !sgn;
@@ -0,0 +1,96 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::{Benchmark, GuestEnv, Runnable};
+
+/// A WebAssembly module exporting `fib: (i32) -> i32`, equivalent to the `fib` benchmark.
+///
+/// ```wat
+/// (module
+///   (func (export "fib") (param $n i32) (result i32)
+///     (local $a i32) (local $b i32) (local $t i32)
+///     (local.set $b (i32.const 1))
+///     (block
+///       (loop
+///         (br_if 1 (i32.eqz (local.get $n)))
+///         (local.set $t (i32.add (local.get $a) (local.get $b)))
+///         (local.set $a (local.get $b))
+///         (local.set $b (local.get $t))
+///         (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+///         (br 0)))
+///     (local.get $b)))
+/// ```
+pub const FIB_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7f, 0x01, 0x7f,
+    0x03, 0x02, 0x01, 0x00, 0x07, 0x07, 0x01, 0x03, 0x66, 0x69, 0x62, 0x00, 0x00, 0x0a, 0x2f, 0x01,
+    0x2d, 0x01, 0x03, 0x7f, 0x41, 0x01, 0x21, 0x02, 0x02, 0x40, 0x03, 0x40, 0x20, 0x00, 0x45, 0x0d,
+    0x01, 0x20, 0x01, 0x20, 0x02, 0x6a, 0x21, 0x03, 0x20, 0x02, 0x21, 0x01, 0x20, 0x03, 0x21, 0x02,
+    0x20, 0x00, 0x41, 0x01, 0x6b, 0x21, 0x00, 0x0c, 0x00, 0x0b, 0x0b, 0x20, 0x02, 0x0b,
+];
+
+#[derive(Args, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Param {
+    /// Fibonacci index, passed to the module as an i32.
+    #[arg(value_parser = clap::value_parser!(u32).range(..=i32::MAX as i64))]
+    pub n: u32,
+}
+
+impl Benchmark for Param {
+    const NAME: &'static str = "wasm";
+
+    type Input = Input;
+
+    fn input(&self) -> (Input, Vec<u32>) {
+        let input = Input {
+            module: FIB_WASM.to_vec(),
+            n: self.n,
+        };
+
+        (input, vec![])
+    }
+
+    fn size(&self) -> u32 {
+        self.n
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Input {
+    pub module: Vec<u8>,
+    pub n: u32,
+}
+
+impl Runnable for Input {
+    type Output = u32;
+
+    fn run<E: GuestEnv>(self, _env: &mut E) -> anyhow::Result<Self::Output> {
+        use wasmi::{Engine, Linker, Module, Store};
+
+        let engine = Engine::default();
+        let module = Module::new(&engine, &self.module[..])?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)?
+            .start(&mut store)?;
+
+        let fib = instance.get_typed_func::<i32, i32>(&store, "fib")?;
+        let n = i32::try_from(self.n)?;
+        let out = fib.call(&mut store, n)?;
+
+        Ok(out as u32)
+    }
+}
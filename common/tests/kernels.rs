@@ -0,0 +1,296 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Known answers for the benchmark kernels, run on the host through the guest entrypoint.
+
+use common::{
+    big_input, big_input_push, big_input_vecless, decompress, env::HostEnv, fib, float,
+    float_fixed, matmul, mlp, mpt, sha2, sort, wasm, Benchmark, BenchmarkInput, Cycles, Runnable,
+};
+
+type Output<B> = <<B as Benchmark>::Input as Runnable>::Output;
+
+fn run<B: Benchmark>(benchmark: B) -> Output<B> {
+    let (input, extra_input) = benchmark.input();
+
+    let mut env = HostEnv::new();
+    env.write::<BenchmarkInput>(&input.into());
+    for val in &extra_input {
+        env.write(val);
+    }
+
+    common::run(&mut env).unwrap();
+
+    let output = env.read_journal().unwrap();
+    env.read_journal::<Cycles>().unwrap();
+    output
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(hex: &str) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+    }
+    bytes
+}
+
+#[test]
+fn fib() {
+    for (n, expected) in [
+        (0, 1),
+        (1, 1),
+        (10, 89),
+        (46, 2971215073),
+        // Wraps around
+        (100, 2425370821),
+        (1000, 1318412525),
+    ] {
+        assert_eq!(run(fib::Param { n }), expected, "fib {}", n);
+    }
+}
+
+#[test]
+fn sha2() {
+    for (n, expected) in [
+        (
+            0,
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        ),
+        (
+            1,
+            "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925",
+        ),
+        (
+            2,
+            "2b32db6c2c0a6235fb1397e8225ea85e0f0e6e8c7b126d0016ccbde0e667151e",
+        ),
+        (
+            100,
+            "2d7695a887c45cb61a80757127afd676bd16341a5e1cf0f8cb6962e5fca42517",
+        ),
+    ] {
+        assert_eq!(hex(&run(sha2::Param { n })), expected, "sha2 {}", n);
+    }
+}
+
+#[test]
+fn sort() {
+    for (n, expected) in [
+        (0, 0),
+        (1, 1346647962),
+        (2, 3197682625),
+        (3, 3620288897),
+        (17, 1774263164),
+        (1000, 2142695386),
+    ] {
+        assert_eq!(run(sort::Param { n }), expected, "sort {}", n);
+    }
+}
+
+#[test]
+fn big_input() {
+    for (words, expected) in [(0, 0), (100, 4950), (100_000, 704982704)] {
+        assert_eq!(run(big_input::Param { words }), expected);
+        assert_eq!(run(big_input_push::Param { words }), expected);
+        assert_eq!(run(big_input_vecless::Param { words }), expected);
+    }
+}
+
+#[test]
+fn matmul() {
+    let run_input = |n, a, b| matmul::Input { n, a, b }.run(&mut HostEnv::new()).unwrap();
+
+    // [1 2; 3 4] x [5 6; 7 8] = [19 22; 43 50]
+    assert_eq!(run_input(2, vec![1, 2, 3, 4], vec![5, 6, 7, 8]), 134);
+    // Wraps around
+    assert_eq!(run_input(1, vec![u32::MAX], vec![2]), u32::MAX - 1);
+    assert!(matmul::Input {
+        n: 2,
+        a: vec![1],
+        b: vec![1]
+    }
+    .run(&mut HostEnv::new())
+    .is_err());
+
+    assert_eq!(run(matmul::Param { n: 0 }), 0);
+}
+
+#[test]
+fn mlp() {
+    const ONE: i32 = 1 << mlp::FRAC_BITS;
+
+    let input = mlp::Input {
+        input: vec![ONE, 2 * ONE],
+        layers: vec![
+            // Identity, with a bias that the ReLU clamps to 0 in the second output
+            mlp::Layer {
+                weights: vec![ONE, 0, 0, ONE],
+                biases: vec![0, -3 * ONE],
+            },
+            // Swaps the outputs
+            mlp::Layer {
+                weights: vec![0, ONE, ONE, 0],
+                biases: vec![ONE, 0],
+            },
+        ],
+    };
+    assert_eq!(
+        input.run(&mut HostEnv::new()).unwrap(),
+        2 * ONE as u32,
+        "[1, 2] -> [1, 0] -> [1, 1]"
+    );
+
+    let bad_shape = mlp::Input {
+        input: vec![ONE, ONE],
+        layers: vec![mlp::Layer {
+            weights: vec![ONE],
+            biases: vec![0, 0],
+        }],
+    };
+    assert!(bad_shape.run(&mut HostEnv::new()).is_err());
+
+    let no_width = mlp::Input {
+        input: vec![],
+        layers: vec![],
+    };
+    assert!(no_width.run(&mut HostEnv::new()).is_err());
+}
+
+#[test]
+fn float() {
+    // Escape iteration counts of the tile, summed, from an independent f64 implementation
+    for (n, expected) in [(0, 0), (1, 1024), (2, 1974), (10, 5990), (100, 30871)] {
+        assert_eq!(run(float::Param { n }), expected, "float {}", n);
+    }
+}
+
+#[test]
+fn float_fixed() {
+    // Q4.27 is precise enough to escape at the same iteration as f64 on this tile
+    for n in [0, 1, 2, 10, 100, 1000] {
+        assert_eq!(
+            run(float_fixed::Param { n }),
+            run(float::Param { n }),
+            "float_fixed {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn mpt() {
+    // Hashes from an independent implementation of the trie, keyed by sha256 of the index.
+    // Accounts 0, 1 and 2 with balances 5, 7 and 3: the key of 0 starts with bit 1, those of
+    // 1 and 2 with bits 01 and 00. Account 0 is set to 9, then account 1 to 11.
+    let root = unhex("543290e0a365ef11435637952f1bd7b58a13f672f87ad8c938cdb0a0a28d8dd8");
+    let updates = vec![
+        mpt::Update {
+            index: 0,
+            old_balance: 5,
+            new_balance: 9,
+            siblings: vec![unhex(
+                "22cdcc712d217dbc4550e4e24cc41ab9e5b6f9e192925de5e52f794f4f758b8e",
+            )],
+        },
+        mpt::Update {
+            index: 1,
+            old_balance: 7,
+            new_balance: 11,
+            siblings: vec![
+                unhex("e8525d1c82ed75802e4ed5e7113b54b98e78fef3d656acfbe2a213381e9389a1"),
+                unhex("97c44e86ed04267bb89f5cb45daedb490f880817647b9d754943618ab5b5ce4c"),
+            ],
+        },
+    ];
+
+    let input = mpt::Input {
+        root,
+        updates: updates.clone(),
+    };
+    assert_eq!(
+        hex(&input.run(&mut HostEnv::new()).unwrap()),
+        "4070a06f84be8dc3f07baa8c33ca73ddc248620d8d7a552993a2f0ceefdb5f1f"
+    );
+
+    // Witness of an old balance
+    let mut stale = updates;
+    stale[1].old_balance = 8;
+    let input = mpt::Input {
+        root,
+        updates: stale,
+    };
+    assert!(input.run(&mut HostEnv::new()).is_err());
+
+    // Accounts 1 and 2 only, whose keys share the first bit, so the root has an empty subtree
+    let input = mpt::Input {
+        root: unhex("92ff9724dc9f6d156326b91ecb14d01b2b25819471c71ed00308ed4ff302ac47"),
+        updates: vec![mpt::Update {
+            index: 1,
+            old_balance: 7,
+            new_balance: 8,
+            siblings: vec![
+                mpt::EMPTY,
+                unhex("97c44e86ed04267bb89f5cb45daedb490f880817647b9d754943618ab5b5ce4c"),
+            ],
+        }],
+    };
+    assert_eq!(
+        hex(&input.run(&mut HostEnv::new()).unwrap()),
+        "a1ee796fb3eefb27108fbb9f322ac14dee428b838a4265a747dafd8b0b8cca5e"
+    );
+
+    // Without updates the root is unchanged, and the generated witnesses are valid
+    for accounts in [1, 2, 100] {
+        let (input, _) = mpt::Param {
+            accounts,
+            updates: 0,
+        }
+        .input();
+        let root = input.root;
+        assert_eq!(input.run(&mut HostEnv::new()).unwrap(), root);
+        run(mpt::Param {
+            accounts,
+            updates: 10,
+        });
+    }
+}
+
+#[test]
+fn decompress() {
+    use ::sha2::{Digest, Sha256};
+
+    assert_eq!(
+        hex(&run(decompress::Param { n: 0 })),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+
+    for n in [1, 100, 100_000] {
+        let payload = decompress::Param { n }.payload();
+        assert_eq!(payload.len(), n as usize);
+
+        let expected: [u8; 32] = Sha256::digest(&payload).into();
+        assert_eq!(run(decompress::Param { n }), expected, "decompress {}", n);
+    }
+}
+
+#[test]
+fn wasm() {
+    for n in [0, 1, 10, 46, 100, 1000] {
+        assert_eq!(run(wasm::Param { n }), run(fib::Param { n }), "wasm {}", n);
+    }
+}
@@ -14,13 +14,8 @@
 
 #![no_main]
 
-use risc0_zkvm::guest::env;
-
 risc0_zkvm::guest::entry!(main);
 
-use common::BenchmarkInput;
-
 pub fn main() {
-    let bench: BenchmarkInput = env::read();
-    bench.run().unwrap();
+    common::run(&mut common::env::R0Env).unwrap();
 }
@@ -6,8 +6,10 @@ VM="${3:-unknown}"
 SEGMENT_SIZE="${4:-unknown}"
 
 export RUSTFLAGS='-C target-cpu=native'
+# Merged once below instead of after every benchmark
+export SKIP_MERGE=1
 
-cargo run -F ${FEATURES} --release -- measure --segment-size=${SEGMENT_SIZE} ${MACHINE_TAG} ${VM} hello-world
+cargo run -F ${FEATURES} --release -- measure --segment-size=${SEGMENT_SIZE} ${MACHINE_TAG} ${VM} hello_world
 
 
 bash -x run-sha2.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
@@ -16,9 +18,28 @@ bash -x run-fib.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
 
 bash -x run-sort.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
 
+bash -x run-matmul.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-mlp.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-float.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-float-fixed.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-wasm.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-mpt.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-compose.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
 
 bash -x run-big-input.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
 
 bash -x run-big-input-push.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
 
 bash -x run-big-input-vecless.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+bash -x run-decompress.sh ${FEATURES} ${MACHINE_TAG} ${VM} ${SEGMENT_SIZE}
+
+# Combine the experiments into measurements.csv
+cargo run -F ${FEATURES} --release -- merge
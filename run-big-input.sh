@@ -17,5 +17,10 @@ SIZES=(
 
 for SIZE in "${SIZES[@]}"
 do
-    cargo run -F ${FEATURES} --release -- measure --segment-size=${SEGMENT_SIZE} ${MACHINE_TAG} ${VM} big-input ${SIZE}
+    cargo run -F ${FEATURES} --release -- measure --segment-size=${SEGMENT_SIZE} ${MACHINE_TAG} ${VM} big_input ${SIZE}
 done
+
+# Combine the experiments into measurements.csv, run-all.sh does it once at the end
+if [ -z "${SKIP_MERGE}" ]; then
+    cargo run -F ${FEATURES} --release -- merge
+fi
@@ -0,0 +1,24 @@
+#!/bin/sh -x
+
+FEATURES="${1:-default}"
+MACHINE_TAG="${2:-unknown}"
+VM="${3:-unknown}"
+SEGMENT_SIZE="${4:-unknown}"
+
+SIZES=(
+    16
+    64
+    256
+    1024
+    4096
+)
+
+for SIZE in "${SIZES[@]}"
+do
+    cargo run -F ${FEATURES} --release -- measure --segment-size=${SEGMENT_SIZE} ${MACHINE_TAG} ${VM} float ${SIZE}
+done
+
+# Combine the experiments into measurements.csv, run-all.sh does it once at the end
+if [ -z "${SKIP_MERGE}" ]; then
+    cargo run -F ${FEATURES} --release -- merge
+fi
@@ -0,0 +1,32 @@
+#!/bin/sh -x
+
+FEATURES="${1:-default}"
+MACHINE_TAG="${2:-unknown}"
+VM="${3:-unknown}"
+SEGMENT_SIZE="${4:-unknown}"
+
+ACCOUNTS=(
+    1024
+    65536
+    1048576
+)
+
+UPDATES=(
+    16
+    64
+    256
+    1024
+)
+
+for ACCOUNT in "${ACCOUNTS[@]}"
+do
+    for UPDATE in "${UPDATES[@]}"
+    do
+        cargo run -F ${FEATURES} --release -- measure --segment-size=${SEGMENT_SIZE} ${MACHINE_TAG} ${VM} mpt ${ACCOUNT} ${UPDATE}
+    done
+done
+
+# Combine the experiments into measurements.csv, run-all.sh does it once at the end
+if [ -z "${SKIP_MERGE}" ]; then
+    cargo run -F ${FEATURES} --release -- merge
+fi